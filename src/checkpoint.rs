@@ -0,0 +1,326 @@
+/* use  **************************************************************************************************/
+
+use crate::dirs;
+use crate::history::SessionConfig;
+use crate::timeline::{SetSpec, TimelineState, WorkoutTimeline};
+use chrono::{DateTime, FixedOffset};
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs,
+  io::ErrorKind,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+/* global const  *****************************************************************************************/
+
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/* struct  ***********************************************************************************************/
+
+/// Where an unfinished terminal workout stood, so `trainer squat --resume` can pick it up.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+  pub started_at: DateTime<FixedOffset>,
+  pub saved_at: DateTime<FixedOffset>,
+  pub exercise: String,
+  /// Workout clock position in seconds, pauses excluded.
+  pub elapsed_secs: f64,
+  pub set: u32,
+  pub rep: u32,
+  pub completed_reps: u32,
+  pub pauses: u32,
+  pub paused_secs: f64,
+  pub config: SessionConfig,
+}
+
+/// What one run got through: a resumed run only counts what it did after picking up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunProgress {
+  pub reps_done: u32,
+  pub reps_total: u32,
+  pub sets_done: u32,
+  pub sets_total: u32,
+  pub active_secs: f64,
+}
+
+/* impl  *************************************************************************************************/
+
+impl Checkpoint {
+  /// Rebuilds the workout from the recorded sets; rests are the gaps between them.
+  pub fn timeline(&self) -> WorkoutTimeline {
+    let sets = &self.config.sets;
+    let specs: Vec<SetSpec> = sets
+      .iter()
+      .enumerate()
+      .map(|(idx, span)| {
+        let rest = sets
+          .get(idx + 1)
+          .map_or(0.0, |next| next.start - (span.start + span.duration));
+        SetSpec {
+          exercise: span.exercise.clone(),
+          reps: span.reps,
+          tempo: span.tempo(),
+          rest_after: Duration::from_secs_f64(rest.max(0.0)),
+        }
+      })
+      .collect();
+    WorkoutTimeline::new(&specs)
+  }
+}
+
+impl RunProgress {
+  /// From where the run started (`before`, the checkpoint position or the beginning) to
+  /// `progress`.
+  pub fn between(
+    timeline: &WorkoutTimeline,
+    before: &TimelineState,
+    progress: &TimelineState,
+  ) -> Self {
+    Self {
+      reps_done: progress.total_completed_reps - before.total_completed_reps,
+      reps_total: timeline.total_reps() - before.total_completed_reps,
+      sets_done: progress.completed_sets - before.completed_sets,
+      sets_total: timeline.sets().len() as u32 - before.completed_sets,
+      active_secs: progress
+        .active_elapsed
+        .saturating_sub(before.active_elapsed)
+        .as_secs_f64(),
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+pub fn checkpoint_path() -> Result<PathBuf> {
+  Ok(dirs::data_dir()?.join(CHECKPOINT_FILE))
+}
+
+/// Replaces the checkpoint atomically, so a crash mid-write leaves the previous one.
+pub fn save(checkpoint: &Checkpoint) -> Result<()> {
+  save_to(&checkpoint_path()?, checkpoint)
+}
+
+pub fn load() -> Result<Option<Checkpoint>> {
+  load_from(&checkpoint_path()?)
+}
+
+pub fn clear() -> Result<()> {
+  clear_at(&checkpoint_path()?)
+}
+
+fn save_to(path: &Path, checkpoint: &Checkpoint) -> Result<()> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir).wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+  }
+  let tmp = path.with_extension("json.tmp");
+  fs::write(&tmp, serde_json::to_vec_pretty(checkpoint)?)
+    .and_then(|_| fs::rename(&tmp, path))
+    .wrap_err_with(|| format!("failed to write {}", path.display()))
+}
+
+fn load_from(path: &Path) -> Result<Option<Checkpoint>> {
+  let data = match fs::read(path) {
+    Ok(data) => data,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+    Err(err) => return Err(err).wrap_err_with(|| format!("failed to read {}", path.display())),
+  };
+  let checkpoint = serde_json::from_slice(&data)
+    .wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+  Ok(Some(checkpoint))
+}
+
+fn clear_at(path: &Path) -> Result<()> {
+  match fs::remove_file(path) {
+    Err(err) if err.kind() != ErrorKind::NotFound => {
+      Err(err).wrap_err_with(|| format!("failed to remove {}", path.display()))
+    }
+    _ => Ok(()),
+  }
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+  use crate::timeline::Tempo;
+
+  fn checkpoint(elapsed_secs: f64) -> Checkpoint {
+    let timeline = WorkoutTimeline::uniform(
+      Tempo {
+        down: 1.0,
+        hold: 1.0,
+        up: 1.0,
+      },
+      2,
+      2,
+      Duration::from_secs(5),
+    );
+    let state = timeline.at(Duration::from_secs_f64(elapsed_secs));
+    let saved_at = DateTime::parse_from_rfc3339("2026-10-18T08:05:00+09:00").unwrap();
+    Checkpoint {
+      started_at: DateTime::parse_from_rfc3339("2026-10-18T08:00:00+09:00").unwrap(),
+      saved_at,
+      exercise: "Slow Squat".to_string(),
+      elapsed_secs,
+      set: state.set,
+      rep: state.rep,
+      completed_reps: state.total_completed_reps,
+      pauses: 1,
+      paused_secs: 2.5,
+      config: SessionConfig::from_timeline(&timeline),
+    }
+  }
+
+  #[test]
+  fn save_load_and_clear_round_trip() {
+    let dir = std::env::temp_dir().join(format!("trainer-checkpoint-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let path = dir.join("nested").join(CHECKPOINT_FILE);
+    assert!(load_from(&path).unwrap().is_none());
+
+    save_to(&path, &checkpoint(7.5)).unwrap();
+    assert!(!path.with_extension("json.tmp").exists());
+    let loaded = load_from(&path).unwrap().unwrap();
+    assert_eq!(
+      (
+        loaded.elapsed_secs,
+        loaded.set,
+        loaded.rep,
+        loaded.completed_reps
+      ),
+      (7.5, 2, 0, 2)
+    );
+    assert_eq!((loaded.pauses, loaded.paused_secs), (1, 2.5));
+    assert_eq!(loaded.config.sets.len(), 2);
+
+    clear_at(&path).unwrap();
+    assert!(load_from(&path).unwrap().is_none());
+    // Clearing twice is fine.
+    clear_at(&path).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn load_rejects_a_corrupt_checkpoint() {
+    let dir = std::env::temp_dir().join(format!("trainer-corrupt-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(CHECKPOINT_FILE);
+    fs::write(&path, "{\"exercise\":").unwrap();
+    assert!(load_from(&path).is_err());
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+  use crate::timeline::Tempo;
+
+  /// Squats then lunges at another tempo, with rests of 5s, 4s and 6s.
+  fn original() -> WorkoutTimeline {
+    let squat = Tempo {
+      down: 2.0,
+      hold: 1.0,
+      up: 1.0,
+    };
+    let lunge = Tempo {
+      down: 1.0,
+      hold: 0.5,
+      up: 1.5,
+    };
+    let set = |exercise: &str, reps, tempo, rest| SetSpec {
+      exercise: exercise.to_string(),
+      reps,
+      tempo,
+      rest_after: Duration::from_secs(rest),
+    };
+    WorkoutTimeline::new(&[
+      set("Slow Squat", 2, squat, 5),
+      set("Slow Squat", 2, squat, 4),
+      set("Lunge", 3, lunge, 6),
+      set("Lunge", 1, lunge, 0),
+    ])
+  }
+
+  fn checkpoint_of(timeline: &WorkoutTimeline, elapsed: Duration) -> Checkpoint {
+    let state = timeline.at(elapsed);
+    let now = DateTime::parse_from_rfc3339("2026-10-18T08:00:00+09:00").unwrap();
+    Checkpoint {
+      started_at: now,
+      saved_at: now,
+      exercise: "Leg day".to_string(),
+      elapsed_secs: elapsed.as_secs_f64(),
+      set: state.set,
+      rep: state.rep,
+      completed_reps: state.total_completed_reps,
+      pauses: 0,
+      paused_secs: 0.0,
+      config: SessionConfig::from_timeline(timeline),
+    }
+  }
+
+  #[test]
+  fn timeline_lines_up_with_the_original() {
+    let original = original();
+    // Partway through the second rep of the third set.
+    let resume_at = Duration::from_secs_f64(29.5);
+    let rebuilt = checkpoint_of(&original, resume_at).timeline();
+    assert_eq!(rebuilt.total(), original.total());
+    assert_eq!(rebuilt.total_reps(), original.total_reps());
+
+    let mut secs = 0.0;
+    while secs <= original.total().as_secs_f64() {
+      let at = Duration::from_secs_f64(secs);
+      let (want, got) = (original.at(at), rebuilt.at(at));
+      assert_eq!(
+        (got.phase, got.set, got.rep, got.exercise),
+        (want.phase, want.set, want.rep, want.exercise),
+        "at {}s",
+        secs
+      );
+      assert_eq!(got.rest_remaining, want.rest_remaining, "at {}s", secs);
+      assert_eq!(
+        got.total_completed_reps, want.total_completed_reps,
+        "at {}s",
+        secs
+      );
+      secs += 0.25;
+    }
+
+    let resumed = rebuilt.at(resume_at);
+    assert_eq!((resumed.set, resumed.rep), (3, 2));
+    assert_eq!(resumed.exercise, "Lunge");
+  }
+
+  #[test]
+  fn run_progress_counts_only_this_runs_sets() {
+    let timeline = original();
+    // Resumed during the REST after set 2, finished at the end.
+    let before = timeline.at(Duration::from_secs(22));
+    let progress = timeline.at(timeline.total());
+    let run = RunProgress::between(&timeline, &before, &progress);
+    assert_eq!(
+      (run.reps_done, run.reps_total, run.sets_done, run.sets_total),
+      (4, 4, 2, 2)
+    );
+    assert_eq!(run.active_secs, 12.0);
+
+    // Resumed mid-set and stopped during the next rest: the partial set counts once done.
+    let before = timeline.at(Duration::from_secs_f64(29.5));
+    let progress = timeline.at(Duration::from_secs(34));
+    let run = RunProgress::between(&timeline, &before, &progress);
+    assert_eq!(
+      (run.reps_done, run.reps_total, run.sets_done, run.sets_total),
+      (2, 3, 1, 2)
+    );
+
+    let start = timeline.at(Duration::ZERO);
+    let run = RunProgress::between(&timeline, &start, &progress);
+    assert_eq!((run.sets_done, run.sets_total), (3, 4));
+  }
+}
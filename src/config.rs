@@ -0,0 +1,61 @@
+/* use  **************************************************************************************************/
+
+use crate::dirs;
+use color_eyre::eyre::{Result, WrapErr};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::PathBuf};
+
+/* global const  *****************************************************************************************/
+
+const CONFIG_FILE: &str = "config.toml";
+
+/* struct  ***********************************************************************************************/
+
+/// Optional user settings from `$XDG_CONFIG_HOME/trainer/config.toml`.
+///
+/// ```toml
+/// [hooks]
+/// on_set_complete = "notify-send 'Set done'"
+/// "on_phase=REST" = "lights dim"
+///
+/// [cues]
+/// tick = "off"
+/// hold = "988:80,0:60,988:80"
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+  /// Event selector → shell command.
+  #[serde(default)]
+  pub hooks: BTreeMap<String, String>,
+  /// Cue name → `--cues` pattern.
+  #[serde(default)]
+  pub cues: BTreeMap<String, String>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl Config {
+  /// A missing file, or no directory to look in, is an empty config.
+  pub fn load() -> Result<Self> {
+    let path = match config_path() {
+      Ok(path) => path,
+      Err(err) => {
+        tracing::debug!("no config file ({:#}), using the defaults", err);
+        return Ok(Self::default());
+      }
+    };
+    let text = match fs::read_to_string(&path) {
+      Ok(text) => text,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+      Err(err) => return Err(err).wrap_err_with(|| format!("failed to read {}", path.display())),
+    };
+    toml::from_str(&text).wrap_err_with(|| format!("invalid config {}", path.display()))
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+pub fn config_path() -> Result<PathBuf> {
+  Ok(dirs::config_dir()?.join(CONFIG_FILE))
+}
@@ -0,0 +1,480 @@
+/* use  **************************************************************************************************/
+
+use clap::ValueEnum;
+use color_eyre::eyre::{Result, eyre};
+use std::{
+  collections::BTreeMap,
+  f64::consts::TAU,
+  fs::OpenOptions,
+  io::{self, Write},
+  process::{Command, Stdio},
+  str::FromStr,
+  sync::mpsc,
+  thread::{self, JoinHandle},
+  time::Duration,
+};
+
+/* global const  *****************************************************************************************/
+
+/// Reads what `--cues tone` writes: raw signed 16-bit little-endian mono at `SAMPLE_RATE`.
+pub const DEFAULT_TONE_COMMAND: &str = "aplay -q -t raw -f S16_LE -r 22050 -c 1";
+const SAMPLE_RATE: u32 = 22050;
+const VOLUME: f64 = 0.3;
+/// Ramps at both ends of a note, so it starts and stops without a click.
+const FADE_SECS: f64 = 0.005;
+const MAX_NOTE_MS: u64 = 5000;
+const BELL: &[u8] = b"\x07";
+
+/* enum  *************************************************************************************************/
+
+/// `--cues`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CueOutput {
+  /// The terminal bell, with cues told apart by count and rhythm.
+  Bell,
+  /// Generated tones piped to `--tone-command`.
+  Tone,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cue {
+  /// Every second of DOWN/HOLD/UP, like the web page's beep.
+  Tick,
+  /// Each second of the start and next-set countdowns.
+  Countdown,
+  Down,
+  Hold,
+  Up,
+  Rest,
+  SetComplete,
+  Complete,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// A tone, or a pause when `freq` is 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Note {
+  freq: f64,
+  duration: Duration,
+}
+
+/// Notes played one after another, written `HZ:MS,HZ:MS,…` (`0:MS` is a pause); `off` is
+/// no sound.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pattern {
+  notes: Vec<Note>,
+}
+
+/// Plays cues on a worker thread, so the render loop never waits for sound. Cues that
+/// pile up while one is playing are skipped for the newest, so sound never lags behind.
+pub struct Cues {
+  patterns: BTreeMap<Cue, Pattern>,
+  queue: Option<mpsc::Sender<Pattern>>,
+  worker: Option<JoinHandle<()>>,
+}
+
+/* impl standard traits  *********************************************************************************/
+
+impl FromStr for Cue {
+  type Err = String;
+
+  fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+    Cue::ALL
+      .into_iter()
+      .find(|cue| cue.name() == value.trim())
+      .ok_or_else(|| {
+        let names: Vec<&str> = Cue::ALL.iter().map(|cue| cue.name()).collect();
+        format!(
+          "unknown cue {:?}; expected one of {}",
+          value,
+          names.join(", ")
+        )
+      })
+  }
+}
+
+impl FromStr for Pattern {
+  type Err = String;
+
+  fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("off") {
+      return Ok(Self::default());
+    }
+    let notes = value
+      .split(',')
+      .map(|note| {
+        let (freq, ms) = note
+          .trim()
+          .split_once(':')
+          .ok_or_else(|| format!("expected HZ:MS, got {:?}", note))?;
+        let freq: f64 = freq
+          .trim()
+          .parse()
+          .map_err(|_| format!("invalid frequency {:?}", freq))?;
+        let ms: u64 = ms
+          .trim()
+          .parse()
+          .map_err(|_| format!("invalid duration {:?}", ms))?;
+        if !(freq == 0.0 || (20.0..=20_000.0).contains(&freq)) {
+          return Err(format!("frequency {} is outside 20-20000 Hz", freq));
+        }
+        if !(1..=MAX_NOTE_MS).contains(&ms) {
+          return Err(format!("duration {} is outside 1-{} ms", ms, MAX_NOTE_MS));
+        }
+        Ok(Note {
+          freq,
+          duration: Duration::from_millis(ms),
+        })
+      })
+      .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(Self { notes })
+  }
+}
+
+impl Drop for Cues {
+  /// Lets the last cue (usually `complete`) finish before the process exits.
+  fn drop(&mut self) {
+    drop(self.queue.take());
+    if let Some(worker) = self.worker.take() {
+      let _ = worker.join();
+    }
+  }
+}
+
+/* impl  *************************************************************************************************/
+
+impl Cue {
+  const ALL: [Cue; 8] = [
+    Cue::Tick,
+    Cue::Countdown,
+    Cue::Down,
+    Cue::Hold,
+    Cue::Up,
+    Cue::Rest,
+    Cue::SetComplete,
+    Cue::Complete,
+  ];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Cue::Tick => "tick",
+      Cue::Countdown => "countdown",
+      Cue::Down => "down",
+      Cue::Hold => "hold",
+      Cue::Up => "up",
+      Cue::Rest => "rest",
+      Cue::SetComplete => "set_complete",
+      Cue::Complete => "complete",
+    }
+  }
+
+  /// Short for the metronome, falling for DOWN, a double beep for HOLD, rising for UP,
+  /// a low tone for REST and arpeggios for finished sets.
+  fn default_pattern(self) -> &'static str {
+    match self {
+      Cue::Tick => "880:60",
+      Cue::Countdown => "1175:80",
+      Cue::Down => "784:90,0:30,587:130",
+      Cue::Hold => "988:80,0:60,988:80",
+      Cue::Up => "587:90,0:30,784:130",
+      Cue::Rest => "523:300",
+      Cue::SetComplete => "784:110,0:30,988:110,0:30,1175:200",
+      Cue::Complete => "784:110,0:30,988:110,0:30,1175:110,0:30,1568:350",
+    }
+  }
+
+  /// For `--cues bell`, where pitch is lost: one bell for DOWN, two for HOLD, three for UP,
+  /// a quick double for the countdown, a slow double for REST and four or six for finished
+  /// sets. The metronome would blur them, so it is silent.
+  fn bell_pattern(self) -> &'static str {
+    match self {
+      Cue::Tick => "off",
+      Cue::Countdown => "880:50,0:100,880:50",
+      Cue::Down => "880:50",
+      Cue::Hold => "880:50,0:250,880:50",
+      Cue::Up => "880:50,0:250,880:50,0:250,880:50",
+      Cue::Rest => "880:50,0:750,880:50",
+      Cue::SetComplete => "880:50,0:150,880:50,0:150,880:50,0:150,880:50",
+      Cue::Complete => "880:50,0:150,880:50,0:150,880:50,0:150,880:50,0:150,880:50,0:150,880:50",
+    }
+  }
+
+  fn pattern(self, output: CueOutput) -> &'static str {
+    match output {
+      CueOutput::Bell => self.bell_pattern(),
+      CueOutput::Tone => self.default_pattern(),
+    }
+  }
+}
+
+impl Pattern {
+  /// Raw PCM for `--tone-command`.
+  fn samples(&self) -> Vec<u8> {
+    let mut pcm = Vec::new();
+    for note in &self.notes {
+      let secs = note.duration.as_secs_f64();
+      let count = (secs * SAMPLE_RATE as f64) as usize;
+      for idx in 0..count {
+        let t = idx as f64 / SAMPLE_RATE as f64;
+        let envelope = (t / FADE_SECS).min((secs - t) / FADE_SECS).clamp(0.0, 1.0);
+        let sample = if note.freq > 0.0 {
+          (TAU * note.freq * t).sin() * VOLUME * envelope
+        } else {
+          0.0
+        };
+        pcm.extend_from_slice(&((sample * i16::MAX as f64) as i16).to_le_bytes());
+      }
+    }
+    pcm
+  }
+
+  /// When each bell rings, from the start of the pattern: one per note that isn't a pause.
+  fn bells(&self) -> Vec<Duration> {
+    let mut at = Duration::ZERO;
+    let mut bells = Vec::new();
+    for note in &self.notes {
+      if note.freq > 0.0 {
+        bells.push(at);
+      }
+      at += note.duration;
+    }
+    bells
+  }
+
+  fn duration(&self) -> Duration {
+    self.notes.iter().map(|note| note.duration).sum()
+  }
+}
+
+impl Cues {
+  /// No output means no sound; `overrides` replace the output's default pattern of their cue.
+  pub fn start(
+    output: Option<CueOutput>,
+    overrides: Vec<(Cue, Pattern)>,
+    tone_command: &str,
+  ) -> Result<Self> {
+    let Some(output) = output else {
+      return Ok(Self {
+        patterns: BTreeMap::new(),
+        queue: None,
+        worker: None,
+      });
+    };
+    let mut patterns = BTreeMap::new();
+    for cue in Cue::ALL {
+      let pattern = cue
+        .pattern(output)
+        .parse()
+        .map_err(|err: String| eyre!(err))?;
+      patterns.insert(cue, pattern);
+    }
+    patterns.extend(overrides);
+
+    let (queue, requests) = mpsc::channel::<Pattern>();
+    let worker = match output {
+      CueOutput::Bell => {
+        // The controlling terminal, so the bell still rings when stdout is a pipe.
+        let mut tty: Box<dyn Write + Send> = match OpenOptions::new().write(true).open("/dev/tty") {
+          Ok(tty) => Box::new(tty),
+          Err(err) => {
+            tracing::debug!("no /dev/tty ({}), ringing the bell on stderr", err);
+            Box::new(io::stderr())
+          }
+        };
+        thread::spawn(move || {
+          while let Some(pattern) = newest(&requests) {
+            ring(&mut *tty, &pattern);
+          }
+        })
+      }
+      CueOutput::Tone => {
+        let command = tone_command.to_string();
+        thread::spawn(move || {
+          while let Some(pattern) = newest(&requests) {
+            if let Err(err) = play(&command, &pattern) {
+              // Later cues are dropped along with the receiver.
+              tracing::warn!("tone cues disabled: {:#}", err);
+              return;
+            }
+          }
+        })
+      }
+    };
+    Ok(Self {
+      patterns,
+      queue: Some(queue),
+      worker: Some(worker),
+    })
+  }
+
+  pub fn play(&self, cue: Cue) {
+    let (Some(queue), Some(pattern)) = (&self.queue, self.patterns.get(&cue)) else {
+      return;
+    };
+    if !pattern.notes.is_empty() {
+      let _ = queue.send(pattern.clone());
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// `event=pattern`, as given to `--cue`.
+pub fn parse_override(event: &str, pattern: &str) -> Result<(Cue, Pattern)> {
+  let cue = event.parse().map_err(|err: String| eyre!(err))?;
+  let pattern = pattern
+    .parse()
+    .map_err(|err: String| eyre!("invalid pattern for cue {}: {}", event, err))?;
+  Ok((cue, pattern))
+}
+
+/// Waits for a cue, then skips to the last one queued; `None` once `Cues` is dropped.
+fn newest(requests: &mpsc::Receiver<Pattern>) -> Option<Pattern> {
+  let pattern = requests.recv().ok()?;
+  Some(requests.try_iter().last().unwrap_or(pattern))
+}
+
+fn ring(tty: &mut dyn Write, pattern: &Pattern) {
+  let mut elapsed = Duration::ZERO;
+  for at in pattern.bells() {
+    thread::sleep(at - elapsed);
+    let _ = tty.write_all(BELL).and_then(|_| tty.flush());
+    elapsed = at;
+  }
+  thread::sleep(pattern.duration() - elapsed);
+}
+
+/// Runs `command` once per cue with the tone on stdin and waits until it has played.
+fn play(command: &str, pattern: &Pattern) -> Result<()> {
+  let mut child = Command::new("sh")
+    .arg("-c")
+    .arg(command)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|err| eyre!("failed to start {:?}: {}", command, err))?;
+  if let Some(mut stdin) = child.stdin.take() {
+    // A player that exits early shows up in its exit status below.
+    let _ = stdin.write_all(&pattern.samples());
+  }
+  let output = child.wait_with_output()?;
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(match stderr.trim() {
+      "" => eyre!("{:?} exited with {}", command, output.status),
+      stderr => eyre!("{:?} exited with {}: {}", command, output.status, stderr),
+    });
+  }
+  Ok(())
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  fn bells(cue: Cue) -> Vec<Duration> {
+    cue.bell_pattern().parse::<Pattern>().unwrap().bells()
+  }
+
+  #[test]
+  fn default_patterns_parse() {
+    for cue in Cue::ALL {
+      for output in [CueOutput::Bell, CueOutput::Tone] {
+        assert!(
+          cue.pattern(output).parse::<Pattern>().is_ok(),
+          "{} {:?}",
+          cue.name(),
+          output
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn bell_patterns_are_unique() {
+    for (idx, cue) in Cue::ALL.into_iter().enumerate() {
+      for other in &Cue::ALL[idx + 1..] {
+        assert_ne!(
+          bells(cue),
+          bells(*other),
+          "{} and {} ring the same",
+          cue.name(),
+          other.name()
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn bell_counts() {
+    let count = |cue| bells(cue).len();
+    assert_eq!(count(Cue::Tick), 0);
+    assert_eq!(count(Cue::Down), 1);
+    assert_eq!(count(Cue::Hold), 2);
+    assert_eq!(count(Cue::Up), 3);
+    assert_eq!(count(Cue::Rest), 2);
+    assert!(bells(Cue::Rest)[1] > bells(Cue::Hold)[1]);
+    assert!(bells(Cue::Countdown)[1] < bells(Cue::Hold)[1]);
+  }
+
+  #[test]
+  fn bells_skip_pauses() {
+    let pattern: Pattern = "880:50,0:100,440:50,660:20".parse().unwrap();
+    assert_eq!(
+      pattern.bells(),
+      [0, 150, 200].map(Duration::from_millis).to_vec()
+    );
+    assert_eq!(pattern.duration(), Duration::from_millis(220));
+  }
+
+  #[test]
+  fn newest_skips_stale_cues() {
+    let (queue, requests) = mpsc::channel::<Pattern>();
+    for pattern in ["440:10", "440:20", "440:30"] {
+      queue.send(pattern.parse().unwrap()).unwrap();
+    }
+    assert_eq!(newest(&requests), Some("440:30".parse().unwrap()));
+    queue.send("440:40".parse().unwrap()).unwrap();
+    drop(queue);
+    assert_eq!(newest(&requests), Some("440:40".parse().unwrap()));
+    assert_eq!(newest(&requests), None);
+  }
+
+  #[test]
+  fn cues_queued_during_a_slow_cue_are_dropped() {
+    let dir = std::env::temp_dir().join(format!("trainer-cues-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("played");
+    let _ = std::fs::remove_file(&log);
+    let patterns: Vec<(Cue, Pattern)> = [
+      (Cue::Down, "440:10"),
+      (Cue::Hold, "440:20"),
+      (Cue::Up, "440:30"),
+    ]
+    .map(|(cue, pattern)| (cue, pattern.parse().unwrap()))
+    .to_vec();
+    let bytes = |cue| {
+      let (_, pattern) = patterns.iter().find(|(known, _)| *known == cue).unwrap();
+      pattern.samples().len().to_string()
+    };
+
+    // Each cue takes half a second to "play" and records how many bytes it got.
+    let command = format!("sleep 0.5; wc -c >> '{}'", log.display());
+    let cues = Cues::start(Some(CueOutput::Tone), patterns.clone(), &command).unwrap();
+    cues.play(Cue::Down);
+    cues.play(Cue::Hold);
+    cues.play(Cue::Up);
+    drop(cues);
+
+    let played = std::fs::read_to_string(&log).unwrap();
+    let played: Vec<&str> = played.lines().map(str::trim).collect();
+    assert!(!played.contains(&bytes(Cue::Hold).as_str()), "{:?}", played);
+    assert_eq!(played.last(), Some(&bytes(Cue::Up).as_str()));
+    let _ = std::fs::remove_dir_all(&dir);
+  }
+}
@@ -0,0 +1,142 @@
+/* global const  *****************************************************************************************/
+
+const GLYPH_ROWS: usize = 7;
+const PIXEL: char = '█';
+/// Terminal cells are about twice as tall as wide, so a pixel is two cells wide.
+const PIXEL_COLS: usize = 2;
+/// 5×7 digits, `#` marking a pixel.
+const DIGITS: [[&str; GLYPH_ROWS]; 10] = [
+  [
+    " ### ", "#   #", "#   #", "#   #", "#   #", "#   #", " ### ",
+  ],
+  [
+    "  #  ", " ##  ", "  #  ", "  #  ", "  #  ", "  #  ", " ### ",
+  ],
+  [
+    " ### ", "#   #", "    #", "   # ", "  #  ", " #   ", "#####",
+  ],
+  [
+    "#### ", "    #", "    #", " ### ", "    #", "    #", "#### ",
+  ],
+  [
+    "   # ", "  ## ", " # # ", "#  # ", "#####", "   # ", "   # ",
+  ],
+  [
+    "#####", "#    ", "#### ", "    #", "    #", "#   #", " ### ",
+  ],
+  [
+    "  ## ", " #   ", "#    ", "#### ", "#   #", "#   #", " ### ",
+  ],
+  [
+    "#####", "    #", "   # ", "  #  ", " #   ", " #   ", " #   ",
+  ],
+  [
+    " ### ", "#   #", "#   #", " ### ", "#   #", "#   #", " ### ",
+  ],
+  [
+    " ### ", "#   #", "#   #", " ####", "    #", "   # ", " ##  ",
+  ],
+];
+const COLON: [&str; GLYPH_ROWS] = [" ", "#", " ", " ", " ", "#", " "];
+const DOT: [&str; GLYPH_ROWS] = [" ", " ", " ", " ", " ", " ", "#"];
+
+/* fn  ***************************************************************************************************/
+
+/// Renders digits, `:` and `.` as block letters, scaled up as far as `cols`×`rows`
+/// allows; `None` when even the smallest size doesn't fit. Other characters are skipped.
+pub fn render(text: &str, cols: usize, rows: usize) -> Option<Vec<String>> {
+  let glyphs: Vec<&[&str; GLYPH_ROWS]> = text
+    .chars()
+    .filter_map(|ch| match ch {
+      '0'..='9' => Some(&DIGITS[ch as usize - '0' as usize]),
+      ':' => Some(&COLON),
+      '.' => Some(&DOT),
+      _ => None,
+    })
+    .collect();
+  if glyphs.is_empty() {
+    return None;
+  }
+  // Glyph widths plus a blank pixel column between glyphs.
+  let pixels_wide = glyphs.iter().map(|glyph| glyph[0].len()).sum::<usize>() + glyphs.len() - 1;
+  let scale = (cols / (pixels_wide * PIXEL_COLS)).min(rows / GLYPH_ROWS);
+  if scale == 0 {
+    return None;
+  }
+
+  let mut lines = Vec::with_capacity(GLYPH_ROWS * scale);
+  for row in 0..GLYPH_ROWS {
+    let mut line = String::new();
+    for (idx, glyph) in glyphs.iter().enumerate() {
+      if idx > 0 {
+        line.push_str(&" ".repeat(PIXEL_COLS * scale));
+      }
+      for pixel in glyph[row].chars() {
+        let cell = if pixel == '#' { PIXEL } else { ' ' };
+        line.extend(std::iter::repeat_n(cell, PIXEL_COLS * scale));
+      }
+    }
+    lines.extend(std::iter::repeat_n(line, scale));
+  }
+  Some(lines)
+}
+
+/// Display width of `render` output; block letters are all single-width.
+pub fn width(lines: &[String]) -> usize {
+  lines.first().map_or(0, |line| line.chars().count())
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  #[test]
+  fn render_draws_pixels_two_cells_wide() {
+    let lines = render("1", 10, 7).unwrap();
+    assert_eq!(
+      lines,
+      [
+        "    ██    ",
+        "  ████    ",
+        "    ██    ",
+        "    ██    ",
+        "    ██    ",
+        "    ██    ",
+        "  ██████  ",
+      ]
+    );
+    assert_eq!(width(&lines), 10);
+  }
+
+  #[test]
+  fn render_scales_to_the_box() {
+    // "1:0" is 5 + 1 + 5 pixels plus two gaps: 26 cells by 7 rows at scale 1.
+    assert_eq!(width(&render("1:0", 26, 7).unwrap()), 26);
+    // The smaller side decides: wide enough for 3, tall enough for 2.
+    let lines = render("1:0", 80, 14).unwrap();
+    assert_eq!((width(&lines), lines.len()), (52, 14));
+    let lines = render("1:0", 80, 40).unwrap();
+    assert_eq!((width(&lines), lines.len()), (78, 21));
+    // Each pixel becomes a scale × 2·scale block.
+    let lines = render("1", 20, 14).unwrap();
+    assert_eq!(lines[0], lines[1]);
+    assert_eq!(lines[0], "        ████        ");
+  }
+
+  #[test]
+  fn render_gives_up_when_the_box_is_too_small() {
+    assert_eq!(render("1:0", 25, 7), None);
+    assert_eq!(render("1:0", 26, 6), None);
+    assert_eq!(render("", 80, 24), None);
+    assert_eq!(render("ab", 80, 24), None);
+  }
+
+  #[test]
+  fn render_skips_other_characters() {
+    assert_eq!(render("1 s", 10, 7), render("1", 10, 7));
+    assert_eq!(render("0.5", 80, 7).map(|lines| width(&lines)), Some(26));
+    assert_eq!(width(&[]), 0);
+  }
+}
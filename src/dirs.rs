@@ -0,0 +1,89 @@
+/* use  **************************************************************************************************/
+
+use color_eyre::eyre::{Result, eyre};
+use std::{env, ffi::OsString, path::PathBuf};
+
+/* global const  *****************************************************************************************/
+
+/// Subdirectory of the XDG base directories that belongs to us.
+const APP_DIR: &str = "trainer";
+
+/* fn  ***************************************************************************************************/
+
+/// `$XDG_CONFIG_HOME`, falling back to `~/.config`.
+pub fn config_home() -> Result<PathBuf> {
+  xdg_home(|key| env::var_os(key), "XDG_CONFIG_HOME", &[".config"])
+}
+
+/// `$XDG_CONFIG_HOME/trainer`.
+pub fn config_dir() -> Result<PathBuf> {
+  Ok(config_home()?.join(APP_DIR))
+}
+
+/// `$XDG_DATA_HOME/trainer`, falling back to `~/.local/share/trainer`.
+pub fn data_dir() -> Result<PathBuf> {
+  let home = xdg_home(
+    |key| env::var_os(key),
+    "XDG_DATA_HOME",
+    &[".local", "share"],
+  )?;
+  Ok(home.join(APP_DIR))
+}
+
+/// `key` if set, else `$HOME` joined with `fallback`; empty values count as unset.
+fn xdg_home(
+  lookup: impl Fn(&str) -> Option<OsString>,
+  key: &str,
+  fallback: &[&str],
+) -> Result<PathBuf> {
+  let var = |key: &str| lookup(key).filter(|value| !value.is_empty());
+  if let Some(dir) = var(key) {
+    return Ok(PathBuf::from(dir));
+  }
+  let home = var("HOME").ok_or_else(|| eyre!("neither {} nor HOME is set", key))?;
+  Ok(
+    fallback
+      .iter()
+      .fold(PathBuf::from(home), |path, part| path.join(part)),
+  )
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+    let vars: Vec<(String, String)> = vars
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect();
+    move |key| {
+      vars
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| OsString::from(value))
+    }
+  }
+
+  #[test]
+  fn xdg_home_prefers_the_variable() {
+    let vars = lookup(&[("XDG_DATA_HOME", "/data"), ("HOME", "/home/me")]);
+    let path = xdg_home(vars, "XDG_DATA_HOME", &[".local", "share"]).unwrap();
+    assert_eq!(path, PathBuf::from("/data"));
+  }
+
+  #[test]
+  fn xdg_home_falls_back_to_home() {
+    let vars = lookup(&[("XDG_DATA_HOME", ""), ("HOME", "/home/me")]);
+    let path = xdg_home(vars, "XDG_DATA_HOME", &[".local", "share"]).unwrap();
+    assert_eq!(path, PathBuf::from("/home/me/.local/share"));
+  }
+
+  #[test]
+  fn xdg_home_needs_a_home() {
+    let err = xdg_home(lookup(&[]), "XDG_CONFIG_HOME", &[".config"]).unwrap_err();
+    assert_eq!(err.to_string(), "neither XDG_CONFIG_HOME nor HOME is set");
+  }
+}
@@ -0,0 +1,230 @@
+/* use  **************************************************************************************************/
+
+use crate::hooks::Hooks;
+use chrono::{DateTime, FixedOffset, Local};
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Serialize, Serializer};
+use std::{
+  fs::OpenOptions,
+  io::{self, BufWriter, Write},
+  time::{Duration, Instant},
+};
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkoutEvent {
+  /// One second of the start or next-set countdown.
+  Countdown,
+  RepStart,
+  /// DOWN, HOLD, UP or REST began.
+  Phase,
+  Pause,
+  Resume,
+  SetComplete,
+  WorkoutComplete,
+  /// Stopped before the end, by the user or a signal.
+  Abort,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// One JSON Lines record: the event, when it happened, then the event's own fields.
+#[derive(Serialize)]
+struct EventLine<'a, T> {
+  event: WorkoutEvent,
+  /// Milliseconds since the trainer started; never jumps with the wall clock.
+  monotonic_ms: f64,
+  time: DateTime<FixedOffset>,
+  #[serde(flatten)]
+  fields: &'a T,
+}
+
+/// `--events` output and hook dispatch; does nothing when there is neither a target nor
+/// a hook.
+pub struct EventLog {
+  writer: Option<Box<dyn Write + Send>>,
+  hooks: Hooks,
+  epoch: Instant,
+}
+
+/* impl  *************************************************************************************************/
+
+impl EventLog {
+  /// `-` writes to stdout, anything else is a file that events are appended to.
+  pub fn open(target: Option<&str>, hooks: Hooks) -> Result<Self> {
+    let writer: Option<Box<dyn Write + Send>> = match target {
+      None => None,
+      Some("-") => Some(Box::new(io::stdout())),
+      Some(path) => {
+        let file = OpenOptions::new()
+          .create(true)
+          .append(true)
+          .open(path)
+          .wrap_err_with(|| format!("failed to open event log {}", path))?;
+        Some(Box::new(BufWriter::new(file)))
+      }
+    };
+    Ok(Self::with_writer(writer, hooks))
+  }
+
+  fn with_writer(writer: Option<Box<dyn Write + Send>>, hooks: Hooks) -> Self {
+    Self {
+      writer,
+      hooks,
+      epoch: Instant::now(),
+    }
+  }
+
+  pub fn emit<T: Serialize>(&mut self, event: WorkoutEvent, fields: &T) {
+    if self.writer.is_none() && self.hooks.is_empty() {
+      return;
+    }
+    let line = EventLine {
+      event,
+      monotonic_ms: self.epoch.elapsed().as_secs_f64() * 1000.0,
+      time: Local::now().fixed_offset(),
+      fields,
+    };
+    if let Some(writer) = &mut self.writer {
+      let result = serde_json::to_writer(&mut *writer, &line)
+        .map_err(io::Error::from)
+        .and_then(|_| writer.write_all(b"\n"))
+        .and_then(|_| writer.flush());
+      if let Err(err) = result {
+        // A reader that went away shouldn't stop the workout.
+        tracing::warn!("event log disabled: {}", err);
+        self.writer = None;
+      }
+    }
+    if !self.hooks.is_empty() {
+      match serde_json::to_value(&line) {
+        Ok(line) => self.hooks.fire(event, &line),
+        Err(err) => tracing::warn!("failed to pass {:?} event to hooks: {}", event, err),
+      }
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// Serializes a `Duration` as fractional seconds.
+pub fn secs<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+  serializer.serialize_f64(value.as_secs_f64())
+}
+
+pub fn opt_secs<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+  match value {
+    Some(value) => secs(value, serializer),
+    None => serializer.serialize_none(),
+  }
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+  use serde_json::Value;
+  use std::sync::{Arc, Mutex};
+
+  #[derive(Clone, Default)]
+  struct Sink(Arc<Mutex<Vec<u8>>>);
+
+  impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.0.lock().unwrap().extend_from_slice(buf);
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[derive(Serialize)]
+  struct Fields {
+    set: u32,
+    phase: &'static str,
+    #[serde(serialize_with = "secs")]
+    remaining_secs: Duration,
+    #[serde(serialize_with = "opt_secs")]
+    rest_remaining_secs: Option<Duration>,
+  }
+
+  fn written(sink: &Sink) -> String {
+    String::from_utf8(sink.0.lock().unwrap().clone()).unwrap()
+  }
+
+  fn parse_lines(text: &str) -> Vec<Value> {
+    assert!(text.ends_with('\n'));
+    text
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn emit_writes_one_object_per_line() {
+    let sink = Sink::default();
+    let mut log = EventLog::with_writer(Some(Box::new(sink.clone())), Hooks::start(Vec::new()));
+    let fields = Fields {
+      set: 2,
+      phase: "HOLD",
+      remaining_secs: Duration::from_millis(1500),
+      rest_remaining_secs: None,
+    };
+    log.emit(WorkoutEvent::Phase, &fields);
+    log.emit(WorkoutEvent::SetComplete, &fields);
+
+    let text = written(&sink);
+    for line in text.lines() {
+      assert!(line.starts_with(r#"{"event":"#), "{}", line);
+    }
+    let lines = parse_lines(&text);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["event"], "phase");
+    assert_eq!(lines[1]["event"], "set_complete");
+    for line in &lines {
+      let object = line.as_object().unwrap();
+      let mut keys: Vec<&str> = object.keys().map(String::as_str).collect();
+      keys.sort_unstable();
+      assert_eq!(
+        keys,
+        [
+          "event",
+          "monotonic_ms",
+          "phase",
+          "remaining_secs",
+          "rest_remaining_secs",
+          "set",
+          "time"
+        ]
+      );
+      assert!(line["monotonic_ms"].as_f64().unwrap() >= 0.0);
+      DateTime::parse_from_rfc3339(line["time"].as_str().unwrap()).unwrap();
+      assert_eq!(line["set"], 2);
+      assert_eq!(line["phase"], "HOLD");
+      assert_eq!(line["remaining_secs"], 1.5);
+      assert_eq!(line["rest_remaining_secs"], Value::Null);
+    }
+    assert!(lines[0]["monotonic_ms"].as_f64() <= lines[1]["monotonic_ms"].as_f64());
+  }
+
+  #[test]
+  fn emit_keeps_the_countdown_fields() {
+    let sink = Sink::default();
+    let mut log = EventLog::with_writer(Some(Box::new(sink.clone())), Hooks::start(Vec::new()));
+    log.emit(
+      WorkoutEvent::Countdown,
+      &serde_json::json!({ "count": 3, "set": 1, "sets": 2 }),
+    );
+
+    let lines = parse_lines(&written(&sink));
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["event"], "countdown");
+    assert_eq!(lines[0]["count"], 3);
+    assert_eq!(lines[0]["sets"], 2);
+  }
+}
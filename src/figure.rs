@@ -0,0 +1,331 @@
+/* use  **************************************************************************************************/
+
+use color_eyre::eyre::{Result, eyre};
+use std::{f64::consts::TAU, time::Duration};
+
+/* global const  *****************************************************************************************/
+
+// Body proportions of the web page's `drawFigure`, in its pixels at scale 1.
+const HEAD_R: f64 = 12.0;
+const TORSO: f64 = 78.0;
+const THIGH: f64 = 80.0;
+const SHIN: f64 = 80.0;
+const SHOULDER: f64 = 34.0;
+const HIP: f64 = 30.0;
+const FOOT: f64 = 14.0;
+const FOOT_SPREAD: f64 = 36.0;
+/// Ground to the top of the head when standing, plus a little headroom.
+const FIGURE_HEIGHT: f64 = 280.0;
+/// Knee to knee at the bottom of the squat, plus room for the tremor.
+const FIGURE_WIDTH: f64 = 230.0;
+/// Braille dots per terminal cell; a dot is about as wide as it is tall.
+const DOTS_X: usize = 2;
+const DOTS_Y: usize = 4;
+const BRAILLE: u32 = 0x2800;
+
+/* struct  ***********************************************************************************************/
+
+/// The fatigue tremor: a sideways shake that grows from `swing_start` to `swing_stop`
+/// (web pixels) over the workout, at `freq` Hz.
+#[derive(Clone, Copy, Debug)]
+pub struct Tremor {
+  pub swing_start: f64,
+  pub swing_stop: f64,
+  pub freq: f64,
+}
+
+/// Joint positions of the figure, laid out like the web page's `drawFigure`.
+#[derive(Clone, Copy, Debug)]
+struct Pose {
+  hip: (f64, f64),
+  shoulder: (f64, f64),
+  hip_l: (f64, f64),
+  hip_r: (f64, f64),
+  knee_l: (f64, f64),
+  knee_r: (f64, f64),
+  ankle_l: (f64, f64),
+  ankle_r: (f64, f64),
+}
+
+/// Braille dot canvas: 2×4 dots per cell.
+struct Canvas {
+  width: usize,
+  height: usize,
+  dots: Vec<bool>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl Tremor {
+  pub fn validate(&self) -> Result<()> {
+    if self.swing_start.is_sign_negative()
+      || self.swing_stop.is_sign_negative()
+      || self.freq.is_sign_negative()
+    {
+      return Err(eyre!("swing-start, swing-stop, and freq must be >= 0"));
+    }
+    if !(self.swing_start.is_finite() && self.swing_stop.is_finite() && self.freq.is_finite()) {
+      return Err(eyre!("swing-start, swing-stop, and freq must be finite"));
+    }
+    Ok(())
+  }
+
+  /// Sideways hip offset in web pixels at `time` on the workout clock. `fatigue` is the
+  /// workout progress (0–1); `calm` scales it down, e.g. toward the end of a rest.
+  pub fn sway(&self, time: Duration, fatigue: f64, calm: f64) -> f64 {
+    let fatigue = fatigue.clamp(0.0, 1.0);
+    let swing = self.swing_start + (self.swing_stop - self.swing_start) * fatigue * fatigue;
+    let base = TAU * self.freq * time.as_secs_f64();
+    (base.sin() + (base * 2.4).sin() * 0.4) * swing * calm
+  }
+}
+
+impl Pose {
+  /// Feet centered on `center` at `ground`; `tremor` is the sideways hip offset in the
+  /// same units, already scaled.
+  fn new(center: f64, ground: f64, scale: f64, depth: f64, tremor: f64) -> Self {
+    let hip_top = ground - (THIGH + SHIN) * scale;
+    let hip_bottom = ground - SHIN * scale + 6.0 * scale;
+    let hip_y = hip_top + (hip_bottom - hip_top) * depth;
+    let hip_x = center + tremor;
+    let shoulder_x = hip_x - depth * 26.0 * scale + tremor * 0.3;
+    let shoulder_y = hip_y - TORSO * scale + depth * 12.0 * scale;
+    let ankle_l = (center - FOOT_SPREAD * scale, ground);
+    let ankle_r = (center + FOOT_SPREAD * scale, ground);
+    let hip_l = (hip_x - HIP * 0.4 * scale, hip_y);
+    let hip_r = (hip_x + HIP * 0.4 * scale, hip_y);
+    Self {
+      hip: (hip_x, hip_y),
+      shoulder: (shoulder_x, shoulder_y),
+      hip_l,
+      hip_r,
+      knee_l: knee(hip_l, ankle_l, THIGH * scale, SHIN * scale, true),
+      knee_r: knee(hip_r, ankle_r, THIGH * scale, SHIN * scale, false),
+      ankle_l,
+      ankle_r,
+    }
+  }
+}
+
+impl Canvas {
+  fn new(cols: usize, rows: usize) -> Self {
+    let (width, height) = (cols * DOTS_X, rows * DOTS_Y);
+    Self {
+      width,
+      height,
+      dots: vec![false; width * height],
+    }
+  }
+
+  fn set(&mut self, x: f64, y: f64) {
+    let (x, y) = (x.round(), y.round());
+    if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+      return;
+    }
+    self.dots[y as usize * self.width + x as usize] = true;
+  }
+
+  fn line(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) {
+    let steps = ((x1 - x0).abs().max((y1 - y0).abs()) * 2.0).ceil().max(1.0);
+    for step in 0..=steps as usize {
+      let t = step as f64 / steps;
+      self.set(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+    }
+  }
+
+  fn circle(&mut self, (cx, cy): (f64, f64), r: f64) {
+    let steps = (TAU * r * 2.0).ceil().max(8.0);
+    for step in 0..steps as usize {
+      let angle = TAU * step as f64 / steps;
+      self.set(cx + r * angle.cos(), cy + r * angle.sin());
+    }
+  }
+
+  /// Cells without dots come out as spaces, not blank braille.
+  fn lines(&self) -> Vec<String> {
+    (0..self.height / DOTS_Y)
+      .map(|row| {
+        (0..self.width / DOTS_X)
+          .map(|col| {
+            let mut bits = 0;
+            for (dx, dy, bit) in [
+              (0, 0, 0x01),
+              (0, 1, 0x02),
+              (0, 2, 0x04),
+              (1, 0, 0x08),
+              (1, 1, 0x10),
+              (1, 2, 0x20),
+              (0, 3, 0x40),
+              (1, 3, 0x80),
+            ] {
+              let (x, y) = (col * DOTS_X + dx, row * DOTS_Y + dy);
+              if self.dots[y * self.width + x] {
+                bits |= bit;
+              }
+            }
+            match bits {
+              0 => ' ',
+              bits => char::from_u32(BRAILLE + bits).unwrap_or(' '),
+            }
+          })
+          .collect()
+      })
+      .collect()
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// Draws the squatter as large as fits in `cols`×`rows` cells, standing on the last row.
+/// `depth` is 0 standing to 1 at the bottom; `sway` comes from [`Tremor::sway`]. The lines
+/// are all as wide as the figure needs, at most `cols`.
+pub fn draw(cols: usize, rows: usize, depth: f64, sway: f64) -> Vec<String> {
+  let (dots_w, dots_h) = ((cols * DOTS_X) as f64, (rows * DOTS_Y) as f64);
+  let scale = (dots_h / FIGURE_HEIGHT).min(dots_w / FIGURE_WIDTH);
+  let cols = ((FIGURE_WIDTH * scale) as usize).div_ceil(DOTS_X).min(cols);
+  let mut canvas = Canvas::new(cols, rows);
+  if scale <= 0.0 || cols == 0 {
+    return canvas.lines();
+  }
+
+  let depth = depth.clamp(0.0, 1.0);
+  let ground = dots_h - 1.0;
+  let center = canvas.width as f64 / 2.0;
+  let Pose {
+    hip: (hip_x, hip_y),
+    shoulder: (shoulder_x, shoulder_y),
+    hip_l,
+    hip_r,
+    knee_l,
+    knee_r,
+    ankle_l,
+    ankle_r,
+  } = Pose::new(center, ground, scale, depth, sway * scale);
+
+  canvas.line((0.0, ground), (canvas.width as f64, ground));
+  canvas.line((shoulder_x, shoulder_y), (hip_x, hip_y));
+  canvas.circle(
+    (shoulder_x, shoulder_y - HEAD_R * 1.6 * scale),
+    HEAD_R * scale,
+  );
+  let arm_top = (shoulder_x, shoulder_y + 6.0 * scale);
+  let arm_drop = shoulder_y + 26.0 * scale + depth * 10.0 * scale;
+  canvas.line(arm_top, (shoulder_x - SHOULDER * 0.5 * scale, arm_drop));
+  canvas.line(arm_top, (shoulder_x + SHOULDER * 0.5 * scale, arm_drop));
+  canvas.line(
+    (hip_x - HIP * 0.5 * scale, hip_y),
+    (hip_x + HIP * 0.5 * scale, hip_y),
+  );
+  canvas.line(hip_l, knee_l);
+  canvas.line(knee_l, ankle_l);
+  canvas.line(hip_r, knee_r);
+  canvas.line(knee_r, ankle_r);
+  for (ankle_x, ankle_y) in [ankle_l, ankle_r] {
+    canvas.line(
+      (ankle_x - FOOT * scale, ankle_y),
+      (ankle_x + FOOT * scale, ankle_y),
+    );
+  }
+  canvas.lines()
+}
+
+/// Two-bone IK like the web page's `kneeFromHip`: the knee where thigh and shin meet,
+/// bending outward (left or right) from the hip-ankle line.
+fn knee(
+  hip: (f64, f64),
+  ankle: (f64, f64),
+  thigh: f64,
+  shin: f64,
+  outward_left: bool,
+) -> (f64, f64) {
+  let (dx, dy) = (ankle.0 - hip.0, ankle.1 - hip.1);
+  let dist = dx.hypot(dy).max(0.001);
+  let d = dist.clamp((thigh - shin).abs() + 0.001, thigh + shin - 0.001);
+  let a = (thigh * thigh - shin * shin + d * d) / (2.0 * d);
+  let h = (thigh * thigh - a * a).max(0.0).sqrt();
+  let (ux, uy) = (dx / d, dy / d);
+  let (px, py) = (hip.0 + a * ux, hip.1 + a * uy);
+  let k1 = (px - h * uy, py + h * ux);
+  let k2 = (px + h * uy, py - h * ux);
+  match outward_left {
+    true if k1.0 < k2.0 => k1,
+    true => k2,
+    false if k1.0 > k2.0 => k1,
+    false => k2,
+  }
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  // Expected values come from the page's `drawFigure` and `kneeFromHip` on a 340×340
+  // canvas (scale 1, ground 292.4, center 170), and on 680×680 with an 8px tremor.
+
+  fn assert_near((x, y): (f64, f64), (js_x, js_y): (f64, f64)) {
+    assert!(
+      (x - js_x).abs() < 1e-9 && (y - js_y).abs() < 1e-9,
+      "({}, {}) != ({}, {})",
+      x,
+      y,
+      js_x,
+      js_y
+    );
+  }
+
+  #[test]
+  fn pose_standing_matches_the_web_page() {
+    let pose = Pose::new(170.0, 292.4, 1.0, 0.0, 0.0);
+    assert_near(pose.hip, (170.0, 132.4));
+    assert_near(pose.shoulder, (170.0, 54.4));
+    assert_near(pose.knee_l, (145.7171559616909, 212.35757339425362));
+    assert_near(pose.knee_r, (194.2828440383091, 212.35757339425362));
+  }
+
+  #[test]
+  fn pose_half_way_matches_the_web_page() {
+    let pose = Pose::new(170.0, 292.4, 1.0, 0.5, 0.0);
+    assert_near(pose.hip, (170.0, 175.4));
+    assert_near(pose.shoulder, (157.0, 103.4));
+    assert_near(pose.knee_l, (93.85283049803905, 223.20314471754645));
+    assert_near(pose.knee_r, (246.14716950196095, 223.20314471754645));
+  }
+
+  #[test]
+  fn pose_at_the_bottom_matches_the_web_page() {
+    let pose = Pose::new(170.0, 292.4, 1.0, 1.0, 0.0);
+    assert_near(pose.hip, (170.0, 218.4));
+    assert_near(pose.shoulder, (144.0, 152.4));
+    assert_near(pose.knee_l, (79.50278916940566, 233.83333702791532));
+    assert_near(pose.knee_r, (260.4972108305943, 233.83333702791532));
+  }
+
+  #[test]
+  fn pose_with_tremor_matches_the_web_page() {
+    let pose = Pose::new(340.0, 584.8, 2.0, 1.0, 8.0);
+    assert_near(pose.hip, (348.0, 436.8));
+    assert_near(pose.shoulder, (298.4, 304.8));
+    assert_near(pose.knee_l, (165.93130347890587, 461.58481753255893));
+    assert_near(pose.knee_r, (527.5777190519725, 474.15737322919654));
+  }
+
+  #[test]
+  fn knee_keeps_the_bones_their_length() {
+    let pose = Pose::new(170.0, 292.4, 1.0, 0.7, 3.0);
+    for (hip, knee, ankle) in [
+      (pose.hip_l, pose.knee_l, pose.ankle_l),
+      (pose.hip_r, pose.knee_r, pose.ankle_r),
+    ] {
+      let thigh = (knee.0 - hip.0).hypot(knee.1 - hip.1);
+      let shin = (ankle.0 - knee.0).hypot(ankle.1 - knee.1);
+      assert!((thigh - THIGH).abs() < 1e-9, "thigh {}", thigh);
+      assert!((shin - SHIN).abs() < 1e-9, "shin {}", shin);
+    }
+    // Out of reach: like the page, the direction is divided by the clamped distance, so
+    // the knee lands past the full thigh length rather than failing.
+    let reaching = knee((0.0, 0.0), (0.0, 400.0), THIGH, SHIN, true);
+    assert_near(reaching, (-0.7071100957727613, 200.0));
+  }
+}
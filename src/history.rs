@@ -0,0 +1,424 @@
+/* use  **************************************************************************************************/
+
+use crate::dirs;
+use crate::timeline::{SetSpan, WorkoutTimeline};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{
+  fmt::Write as _,
+  fs::{self, OpenOptions},
+  io::{BufRead, BufReader, ErrorKind, Write},
+  path::{Path, PathBuf},
+};
+use unicode_width::UnicodeWidthChar;
+
+/* global const  *****************************************************************************************/
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionSource {
+  Terminal,
+  Web,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+  Completed,
+  Aborted,
+  /// Cut short by a shutdown signal (SIGTERM, SIGHUP) rather than by the user.
+  Interrupted,
+}
+
+/* struct  ***********************************************************************************************/
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionConfig {
+  pub total_secs: f64,
+  pub sets: Vec<SetSpan>,
+}
+
+/// One line of the history log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionRecord {
+  pub started_at: DateTime<FixedOffset>,
+  pub ended_at: DateTime<FixedOffset>,
+  pub source: SessionSource,
+  pub exercise: String,
+  pub status: SessionStatus,
+  pub reps_done: u32,
+  pub reps_total: u32,
+  pub sets_done: u32,
+  pub sets_total: u32,
+  pub pauses: u32,
+  pub paused_secs: f64,
+  pub active_secs: f64,
+  pub config: SessionConfig,
+}
+
+#[derive(Debug, Default)]
+pub struct SessionFilter {
+  pub since: Option<NaiveDate>,
+  pub until: Option<NaiveDate>,
+  pub exercise: Option<String>,
+  pub completed_only: bool,
+}
+
+/* impl  *************************************************************************************************/
+
+impl SessionStatus {
+  pub fn label(self) -> &'static str {
+    match self {
+      SessionStatus::Completed => "completed",
+      SessionStatus::Aborted => "aborted",
+      SessionStatus::Interrupted => "interrupted",
+    }
+  }
+}
+
+impl SessionSource {
+  pub fn label(self) -> &'static str {
+    match self {
+      SessionSource::Terminal => "terminal",
+      SessionSource::Web => "web",
+    }
+  }
+}
+
+impl SessionFilter {
+  pub fn matches(&self, record: &SessionRecord) -> bool {
+    let date = record.started_at.date_naive();
+    if self.since.is_some_and(|since| date < since) || self.until.is_some_and(|until| date > until)
+    {
+      return false;
+    }
+    if self.completed_only && record.status != SessionStatus::Completed {
+      return false;
+    }
+    if let Some(exercise) = &self.exercise {
+      let needle = exercise.to_lowercase();
+      let hit = record.exercise.to_lowercase().contains(&needle)
+        || record
+          .config
+          .sets
+          .iter()
+          .any(|set| set.exercise.to_lowercase().contains(&needle));
+      if !hit {
+        return false;
+      }
+    }
+    true
+  }
+}
+
+impl SessionConfig {
+  pub fn from_timeline(timeline: &WorkoutTimeline) -> Self {
+    Self {
+      total_secs: timeline.total().as_secs_f64(),
+      sets: timeline.sets().to_vec(),
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+pub fn history_path() -> Result<PathBuf> {
+  Ok(dirs::data_dir()?.join(HISTORY_FILE))
+}
+
+pub fn append(record: &SessionRecord) -> Result<()> {
+  let path = history_path()?;
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir).wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+  }
+  let mut line = serde_json::to_string(record)?;
+  line.push('\n');
+  OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&path)
+    .and_then(|mut file| file.write_all(line.as_bytes()))
+    .wrap_err_with(|| format!("failed to append to {}", path.display()))?;
+  Ok(())
+}
+
+/// Reads the history log; malformed lines are skipped with a warning.
+pub fn load() -> Result<Vec<SessionRecord>> {
+  load_from(&history_path()?)
+}
+
+fn load_from(path: &Path) -> Result<Vec<SessionRecord>> {
+  let file = match fs::File::open(path) {
+    Ok(file) => file,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(err) => return Err(err).wrap_err_with(|| format!("failed to open {}", path.display())),
+  };
+  let mut records = Vec::new();
+  for (idx, line) in BufReader::new(file).lines().enumerate() {
+    let line = line.wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    match serde_json::from_str::<SessionRecord>(&line) {
+      Ok(record) => records.push(record),
+      Err(err) => tracing::warn!(
+        "{}:{}: skipping malformed record: {}",
+        path.display(),
+        idx + 1,
+        err
+      ),
+    }
+  }
+  Ok(records)
+}
+
+pub fn format_table(records: &[SessionRecord]) -> String {
+  let mut output = String::new();
+  let _ = writeln!(
+    output,
+    "{:<16}  {:<8}  {:<20}  {:<11}  {:>9}  {:>5}  {:>8}  {:>6}",
+    "STARTED", "SOURCE", "EXERCISE", "STATUS", "REPS", "SETS", "TUT", "PAUSES"
+  );
+  for record in records {
+    let _ = writeln!(
+      output,
+      "{:<16}  {:<8}  {}  {:<11}  {:>9}  {:>5}  {:>8}  {:>6}",
+      record.started_at.format("%Y-%m-%d %H:%M").to_string(),
+      record.source.label(),
+      fit(&record.exercise, 20),
+      record.status.label(),
+      format!("{}/{}", record.reps_done, record.reps_total),
+      format!("{}/{}", record.sets_done, record.sets_total),
+      format_hms(record.active_secs),
+      record.pauses
+    );
+  }
+  output
+}
+
+pub fn format_hms(secs: f64) -> String {
+  let total = secs.max(0.0).round() as u64;
+  format!(
+    "{}:{:02}:{:02}",
+    total / 3600,
+    (total / 60) % 60,
+    total % 60
+  )
+}
+
+/// `value` padded or cut to exactly `cols` terminal columns, so wide glyphs keep the table
+/// aligned; a cut is marked with `~`.
+fn fit(value: &str, cols: usize) -> String {
+  let width = |ch: char| ch.width().unwrap_or(0);
+  let mut out = String::new();
+  let mut used = 0;
+  if value.chars().map(width).sum::<usize>() > cols {
+    for ch in value.chars() {
+      if used + width(ch) > cols.saturating_sub(1) {
+        break;
+      }
+      out.push(ch);
+      used += width(ch);
+    }
+    out.push('~');
+    used += 1;
+  } else {
+    out.push_str(value);
+    used = value.chars().map(width).sum();
+  }
+  out.extend(std::iter::repeat_n(' ', cols.saturating_sub(used)));
+  out
+}
+
+/// Appends `record`, logging instead of failing so a full disk never costs a workout.
+pub fn record(record: &SessionRecord) {
+  match append(record) {
+    Ok(()) => tracing::info!(
+      "recorded {:?} session: {}/{} reps",
+      record.status,
+      record.reps_done,
+      record.reps_total
+    ),
+    Err(err) => tracing::warn!("failed to record session history: {:#}", err),
+  }
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+  use crate::timeline::Tempo;
+
+  #[test]
+  fn fit_pads_and_cuts_to_columns() {
+    assert_eq!(fit("Squat", 8), "Squat   ");
+    assert_eq!(fit("Slow Squat", 6), "Slow ~");
+    assert_eq!(fit("Lunge", 5), "Lunge");
+  }
+
+  #[test]
+  fn fit_counts_wide_glyphs_twice() {
+    assert_eq!(fit("スクワット", 12), "スクワット  ");
+    // The cut never splits a glyph: 4 columns of text, `~` and a pad.
+    assert_eq!(fit("スクワット", 6), "スク~ ");
+    assert_eq!(fit("スクワット", 7), "スクワ~");
+  }
+
+  #[test]
+  fn load_from_skips_malformed_lines() {
+    let dir = std::env::temp_dir().join(format!("trainer-history-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(HISTORY_FILE);
+    let timeline = WorkoutTimeline::uniform(
+      Tempo {
+        down: 1.0,
+        hold: 1.0,
+        up: 1.0,
+      },
+      2,
+      1,
+      std::time::Duration::ZERO,
+    );
+    let started_at = DateTime::parse_from_rfc3339("2026-10-17T08:00:00+09:00").unwrap();
+    let record = SessionRecord {
+      started_at,
+      ended_at: started_at,
+      source: SessionSource::Web,
+      exercise: "Slow Squat".to_string(),
+      status: SessionStatus::Completed,
+      reps_done: 2,
+      reps_total: 2,
+      sets_done: 1,
+      sets_total: 1,
+      pauses: 0,
+      paused_secs: 0.0,
+      active_secs: 6.0,
+      config: SessionConfig::from_timeline(&timeline),
+    };
+    let line = serde_json::to_string(&record).unwrap();
+    fs::write(
+      &path,
+      format!("{}\n{{\"started_at\":\n\nnot json\n{}\n", line, line),
+    )
+    .unwrap();
+
+    let records = load_from(&path).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].config.sets[0].reps, 2);
+    assert!(load_from(&dir.join("missing.jsonl")).unwrap().is_empty());
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  fn record(started_at: &str, exercise: &str, status: SessionStatus) -> SessionRecord {
+    let started_at = DateTime::parse_from_rfc3339(started_at).unwrap();
+    SessionRecord {
+      started_at,
+      ended_at: started_at,
+      source: SessionSource::Terminal,
+      exercise: exercise.to_string(),
+      status,
+      reps_done: 8,
+      reps_total: 10,
+      sets_done: 1,
+      sets_total: 2,
+      pauses: 1,
+      paused_secs: 5.0,
+      active_secs: 3725.4,
+      config: SessionConfig {
+        total_secs: 100.0,
+        sets: Vec::new(),
+      },
+    }
+  }
+
+  fn date(value: &str) -> Option<NaiveDate> {
+    Some(value.parse().unwrap())
+  }
+
+  #[test]
+  fn matches_date_range_by_local_start_date() {
+    let filter = SessionFilter {
+      since: date("2026-10-10"),
+      until: date("2026-10-17"),
+      ..SessionFilter::default()
+    };
+    let at = |started_at| record(started_at, "Slow Squat", SessionStatus::Completed);
+    assert!(filter.matches(&at("2026-10-10T00:00:00+09:00")));
+    assert!(filter.matches(&at("2026-10-17T23:59:00+09:00")));
+    assert!(!filter.matches(&at("2026-10-09T23:59:00+09:00")));
+    assert!(!filter.matches(&at("2026-10-18T00:00:00+09:00")));
+  }
+
+  #[test]
+  fn matches_status_and_exercise() {
+    let completed_only = SessionFilter {
+      completed_only: true,
+      ..SessionFilter::default()
+    };
+    let started_at = "2026-10-17T08:00:00+09:00";
+    assert!(completed_only.matches(&record(started_at, "Squat", SessionStatus::Completed)));
+    assert!(!completed_only.matches(&record(started_at, "Squat", SessionStatus::Aborted)));
+    assert!(!completed_only.matches(&record(started_at, "Squat", SessionStatus::Interrupted)));
+
+    let lunges = SessionFilter {
+      exercise: Some("LUNGE".to_string()),
+      ..SessionFilter::default()
+    };
+    assert!(lunges.matches(&record(started_at, "Walking lunge", SessionStatus::Aborted)));
+    assert!(!lunges.matches(&record(started_at, "Slow Squat", SessionStatus::Aborted)));
+    assert!(SessionFilter::default().matches(&record(started_at, "", SessionStatus::Aborted)));
+  }
+
+  #[test]
+  fn format_hms_rounds_to_seconds() {
+    assert_eq!(format_hms(0.0), "0:00:00");
+    assert_eq!(format_hms(59.6), "0:01:00");
+    assert_eq!(format_hms(3725.4), "1:02:05");
+    assert_eq!(format_hms(-3.0), "0:00:00");
+  }
+
+  #[test]
+  fn format_table_aligns_columns() {
+    let records = [
+      record(
+        "2026-10-17T08:00:00+09:00",
+        "Slow Squat",
+        SessionStatus::Completed,
+      ),
+      record(
+        "2026-10-18T08:00:00+09:00",
+        "スクワット",
+        SessionStatus::Interrupted,
+      ),
+      record(
+        "2026-10-18T09:00:00+09:00",
+        "A very long exercise name",
+        SessionStatus::Aborted,
+      ),
+    ];
+    let table = format_table(&records);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(
+      lines,
+      [
+        "STARTED           SOURCE    EXERCISE              STATUS            REPS   SETS       TUT  PAUSES",
+        "2026-10-17 08:00  terminal  Slow Squat            completed         8/10    1/2   1:02:05       1",
+        "2026-10-18 08:00  terminal  スクワット            interrupted       8/10    1/2   1:02:05       1",
+        "2026-10-18 09:00  terminal  A very long exercis~  aborted           8/10    1/2   1:02:05       1",
+      ]
+    );
+  }
+}
@@ -0,0 +1,341 @@
+/* use  **************************************************************************************************/
+
+use crate::events::WorkoutEvent;
+use color_eyre::eyre::{Result, eyre};
+use serde_json::Value;
+use std::{
+  process::{Command, Stdio},
+  sync::mpsc,
+  thread::{self, JoinHandle},
+};
+
+/* global const  *****************************************************************************************/
+
+const ENV_PREFIX: &str = "TRAINER_";
+/// Selector names users write, e.g. `on_set_complete` or `on_phase=HOLD`.
+const SELECTORS: [(&str, WorkoutEvent); 8] = [
+  ("on_countdown", WorkoutEvent::Countdown),
+  ("on_rep_start", WorkoutEvent::RepStart),
+  ("on_phase", WorkoutEvent::Phase),
+  ("on_pause", WorkoutEvent::Pause),
+  ("on_resume", WorkoutEvent::Resume),
+  ("on_set_complete", WorkoutEvent::SetComplete),
+  ("on_complete", WorkoutEvent::WorkoutComplete),
+  ("on_abort", WorkoutEvent::Abort),
+];
+
+/* struct  ***********************************************************************************************/
+
+#[derive(Clone, Debug)]
+pub struct HookSpec {
+  selector: String,
+  event: WorkoutEvent,
+  /// Only for `on_phase=<PHASE>`.
+  phase: Option<String>,
+  command: String,
+}
+
+struct Job {
+  selector: String,
+  command: String,
+  env: Vec<(String, String)>,
+}
+
+/// Runs hook commands off the render loop: `fire` only queues, a dispatcher thread spawns
+/// `sh -c <command>` and one thread per child waits for it and logs failures.
+pub struct Hooks {
+  specs: Vec<HookSpec>,
+  jobs: Option<mpsc::Sender<Job>>,
+  dispatcher: Option<JoinHandle<()>>,
+}
+
+/* impl standard traits  *********************************************************************************/
+
+impl Drop for Hooks {
+  /// Makes sure hooks for the last events (`on_complete`, `on_abort`) get started before
+  /// the process exits; it doesn't wait for them to finish.
+  fn drop(&mut self) {
+    drop(self.jobs.take());
+    if let Some(dispatcher) = self.dispatcher.take() {
+      let _ = dispatcher.join();
+    }
+  }
+}
+
+/* impl  *************************************************************************************************/
+
+impl HookSpec {
+  pub fn parse(selector: &str, command: &str) -> Result<Self> {
+    let (name, phase) = match selector.split_once('=') {
+      Some((name, phase)) => (name.trim(), Some(phase.trim().to_ascii_uppercase())),
+      None => (selector.trim(), None),
+    };
+    let event = SELECTORS
+      .iter()
+      .find(|(known, _)| *known == name)
+      .map(|(_, event)| *event)
+      .ok_or_else(|| {
+        let known: Vec<&str> = SELECTORS.iter().map(|(known, _)| *known).collect();
+        eyre!(
+          "unknown hook {:?}; expected one of {}",
+          selector,
+          known.join(", ")
+        )
+      })?;
+    if let Some(phase) = &phase {
+      if event != WorkoutEvent::Phase {
+        return Err(eyre!(
+          "only on_phase takes a phase filter, got {:?}",
+          selector
+        ));
+      }
+      if !["DOWN", "HOLD", "UP", "REST"].contains(&phase.as_str()) {
+        return Err(eyre!("unknown phase {:?} in hook {:?}", phase, selector));
+      }
+    }
+    if command.trim().is_empty() {
+      return Err(eyre!("hook {:?} has an empty command", selector));
+    }
+    Ok(Self {
+      selector: selector.to_string(),
+      event,
+      phase,
+      command: command.to_string(),
+    })
+  }
+
+  fn matches(&self, event: WorkoutEvent, fields: &Value) -> bool {
+    self.event == event
+      && self
+        .phase
+        .as_deref()
+        .is_none_or(|phase| fields["phase"].as_str() == Some(phase))
+  }
+}
+
+impl Hooks {
+  pub fn start(specs: Vec<HookSpec>) -> Self {
+    if specs.is_empty() {
+      return Self {
+        specs,
+        jobs: None,
+        dispatcher: None,
+      };
+    }
+    let (jobs, queue) = mpsc::channel::<Job>();
+    let dispatcher = thread::spawn(move || {
+      for job in queue {
+        run(job);
+      }
+    });
+    Self {
+      specs,
+      jobs: Some(jobs),
+      dispatcher: Some(dispatcher),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.specs.is_empty()
+  }
+
+  /// Queues every hook that matches; `line` is the event's JSON Lines record, whose fields
+  /// become `TRAINER_*` variables.
+  pub fn fire(&self, event: WorkoutEvent, line: &Value) {
+    let Some(jobs) = &self.jobs else {
+      return;
+    };
+    for spec in self.specs.iter().filter(|spec| spec.matches(event, line)) {
+      let job = Job {
+        selector: spec.selector.clone(),
+        command: spec.command.clone(),
+        env: hook_env(line),
+      };
+      if jobs.send(job).is_err() {
+        return;
+      }
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// `TRAINER_EVENT=phase`, `TRAINER_SET=2`, `TRAINER_PHASE=HOLD`, … plus the whole record
+/// as `TRAINER_EVENT_JSON`.
+fn hook_env(line: &Value) -> Vec<(String, String)> {
+  let mut env = vec![(format!("{}EVENT_JSON", ENV_PREFIX), line.to_string())];
+  if let Value::Object(fields) = line {
+    for (key, value) in fields {
+      let value = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+      };
+      env.push((format!("{}{}", ENV_PREFIX, key.to_ascii_uppercase()), value));
+    }
+  }
+  env
+}
+
+fn run(job: Job) {
+  let child = Command::new("sh")
+    .arg("-c")
+    .arg(&job.command)
+    .envs(job.env)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn();
+  let child = match child {
+    Ok(child) => child,
+    Err(err) => {
+      tracing::warn!("hook {} failed to start: {}", job.selector, err);
+      return;
+    }
+  };
+  tracing::debug!("hook {} started: {}", job.selector, job.command);
+  thread::spawn(move || match child.wait_with_output() {
+    Ok(output) if output.status.success() => {
+      tracing::debug!("hook {} finished", job.selector);
+    }
+    Ok(output) => tracing::warn!(
+      "hook {} exited with {}: {}",
+      job.selector,
+      output.status,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ),
+    Err(err) => tracing::warn!("hook {} failed: {}", job.selector, err),
+  });
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+  use serde_json::json;
+  use std::{
+    fs,
+    time::{Duration, Instant},
+  };
+
+  #[test]
+  fn matches_without_a_phase_filter() {
+    let spec = HookSpec::parse("on_phase", "true").unwrap();
+    assert!(spec.matches(WorkoutEvent::Phase, &json!({ "phase": "DOWN" })));
+    assert!(spec.matches(WorkoutEvent::Phase, &json!({ "phase": "REST" })));
+    assert!(!spec.matches(WorkoutEvent::RepStart, &json!({ "phase": "DOWN" })));
+  }
+
+  #[test]
+  fn matches_with_a_phase_filter() {
+    let spec = HookSpec::parse("on_phase=hold", "true").unwrap();
+    assert!(spec.matches(WorkoutEvent::Phase, &json!({ "phase": "HOLD" })));
+    assert!(!spec.matches(WorkoutEvent::Phase, &json!({ "phase": "UP" })));
+    assert!(!spec.matches(WorkoutEvent::Phase, &json!({ "set": 1 })));
+    assert!(!spec.matches(WorkoutEvent::Pause, &json!({ "phase": "HOLD" })));
+  }
+
+  #[test]
+  fn hook_env_exports_every_field() {
+    let line = json!({
+      "event": "phase",
+      "set": 2,
+      "phase": "HOLD",
+      "remaining_secs": 1.5,
+      "rest_remaining_secs": null,
+      "paused": false,
+    });
+    let mut env = hook_env(&line);
+    assert_eq!(
+      env.remove(0),
+      ("TRAINER_EVENT_JSON".to_string(), line.to_string())
+    );
+    env.sort();
+    let expected = [
+      ("TRAINER_EVENT", "phase"),
+      ("TRAINER_PAUSED", "false"),
+      ("TRAINER_PHASE", "HOLD"),
+      ("TRAINER_REMAINING_SECS", "1.5"),
+      ("TRAINER_REST_REMAINING_SECS", ""),
+      ("TRAINER_SET", "2"),
+    ];
+    let expected: Vec<(String, String)> = expected
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect();
+    assert_eq!(env, expected);
+  }
+
+  #[test]
+  fn fire_does_not_wait_for_the_command() {
+    let dir = std::env::temp_dir().join(format!("trainer-hooks-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("phase");
+    let _ = fs::remove_file(&out);
+
+    let hooks = Hooks::start(vec![
+      HookSpec::parse("on_phase", "sleep 2").unwrap(),
+      HookSpec::parse(
+        "on_phase=HOLD",
+        &format!("printf %s \"$TRAINER_PHASE\" > '{}'", out.display()),
+      )
+      .unwrap(),
+    ]);
+    let started = Instant::now();
+    hooks.fire(
+      WorkoutEvent::Phase,
+      &json!({ "event": "phase", "phase": "HOLD" }),
+    );
+    drop(hooks);
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    while fs::read_to_string(&out).unwrap_or_default() != "HOLD" {
+      assert!(started.elapsed() < Duration::from_secs(5), "hook never ran");
+      thread::sleep(Duration::from_millis(20));
+    }
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  #[test]
+  fn parse_rejects_unknown_hooks() {
+    let err = HookSpec::parse("on_finish", "true").unwrap_err();
+    assert!(
+      err
+        .to_string()
+        .starts_with("unknown hook \"on_finish\"; expected one of on_countdown, on_rep_start"),
+      "{}",
+      err
+    );
+  }
+
+  #[test]
+  fn parse_rejects_bad_phase_filters() {
+    let err = HookSpec::parse("on_phase=SIDEWAYS", "true").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "unknown phase \"SIDEWAYS\" in hook \"on_phase=SIDEWAYS\""
+    );
+    let err = HookSpec::parse("on_pause=HOLD", "true").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "only on_phase takes a phase filter, got \"on_pause=HOLD\""
+    );
+    let err = HookSpec::parse("on_phase=UP", "  ").unwrap_err();
+    assert_eq!(err.to_string(), "hook \"on_phase=UP\" has an empty command");
+  }
+
+  #[test]
+  fn parse_normalizes_the_phase() {
+    let spec = HookSpec::parse(" on_phase = rest ", "true").unwrap();
+    assert_eq!(spec.event, WorkoutEvent::Phase);
+    assert_eq!(spec.phase.as_deref(), Some("REST"));
+  }
+}
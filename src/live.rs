@@ -0,0 +1,304 @@
+/* use  **************************************************************************************************/
+
+use crate::timeline::Phase;
+use crate::web::EventStream;
+use serde::{Deserialize, Serialize};
+use std::{
+  sync::{Condvar, Mutex},
+  time::Duration,
+};
+
+/* global const  *****************************************************************************************/
+
+const WAKE_INTERVAL: Duration = Duration::from_secs(1);
+const PING_EVERY: u32 = 15;
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveStatus {
+  Waiting,
+  Countdown,
+  Running,
+  Paused,
+  Rest,
+  Complete,
+  Stopped,
+}
+
+/// Sent to `/api/control` by the workout page and `/remote`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteCommand {
+  Start,
+  Pause,
+  Resume,
+  Skip,
+  Stop,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// What the page running a workout publishes for viewers on other devices.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveState {
+  pub status: LiveStatus,
+  pub exercise: String,
+  pub phase: Phase,
+  pub set: u32,
+  pub sets: u32,
+  pub rep: u32,
+  pub reps: u32,
+  pub time_left_ms: f64,
+  pub rest_left_ms: f64,
+  pub countdown: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoteRequest {
+  pub command: RemoteCommand,
+}
+
+/// Every change bumps `version`; streams remember the last version they sent.
+#[derive(Default)]
+struct Snapshot {
+  version: u64,
+  /// Latest value per event name (`state`, `session`), sent to every new subscriber.
+  latest: Vec<(&'static str, u64, String)>,
+  closed: bool,
+}
+
+/// Latest published values plus a condvar that wakes every subscribed stream.
+#[derive(Default)]
+pub struct LiveHub {
+  snapshot: Mutex<Snapshot>,
+  changed: Condvar,
+}
+
+/* impl  *************************************************************************************************/
+
+impl LiveHub {
+  /// Replaces the latest `event` value; subscribers only ever see the newest one.
+  pub fn publish_latest<T: Serialize>(&self, event: &'static str, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+      self.publish_json(event, json);
+    }
+  }
+
+  /// Publishes every latest value of this hub on `other` as well.
+  pub fn copy_latest_to(&self, other: &LiveHub) {
+    let latest = self
+      .snapshot
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .latest
+      .clone();
+    for (event, _, json) in latest {
+      other.publish_json(event, json);
+    }
+  }
+
+  fn publish_json(&self, event: &'static str, json: String) {
+    let mut snapshot = self.snapshot.lock().unwrap_or_else(|err| err.into_inner());
+    snapshot.version += 1;
+    let version = snapshot.version;
+    snapshot.latest.retain(|(name, _, _)| *name != event);
+    snapshot.latest.push((event, version, json));
+    self.changed.notify_all();
+  }
+
+  /// Ends every open stream; called when the server shuts down.
+  pub fn close(&self) {
+    let mut snapshot = self.snapshot.lock().unwrap_or_else(|err| err.into_inner());
+    snapshot.closed = true;
+    self.changed.notify_all();
+  }
+
+  /// Sends the latest values, then every new one, until the client goes away or
+  /// the hub closes.
+  pub fn serve(&self, mut stream: EventStream) {
+    let (mut sent, first) = {
+      let snapshot = self.snapshot.lock().unwrap_or_else(|err| err.into_inner());
+      let first: Vec<(&str, String)> = snapshot
+        .latest
+        .iter()
+        .map(|(event, _, json)| (*event, json.clone()))
+        .collect();
+      (snapshot.version, first)
+    };
+    if first
+      .iter()
+      .try_for_each(|(event, json)| stream.send(event, json))
+      .is_err()
+    {
+      return;
+    }
+    let mut idle = 0;
+    loop {
+      let (events, closed) = {
+        let snapshot = self.snapshot.lock().unwrap_or_else(|err| err.into_inner());
+        let (snapshot, _) = self
+          .changed
+          .wait_timeout_while(snapshot, WAKE_INTERVAL, |snapshot| {
+            !snapshot.closed && snapshot.version == sent
+          })
+          .unwrap_or_else(|err| err.into_inner());
+        let events: Vec<(&str, String)> = snapshot
+          .latest
+          .iter()
+          .filter(|(_, version, _)| *version > sent)
+          .map(|(event, _, json)| (*event, json.clone()))
+          .collect();
+        sent = snapshot.version;
+        (events, snapshot.closed)
+      };
+      // Values published right before closing (the final session state) still go out.
+      if closed {
+        let _ = events
+          .iter()
+          .try_for_each(|(event, json)| stream.send(event, json));
+        return;
+      }
+      let result = if events.is_empty() {
+        idle += 1;
+        if idle < PING_EVERY {
+          continue;
+        }
+        idle = 0;
+        stream.ping()
+      } else {
+        idle = 0;
+        events
+          .iter()
+          .try_for_each(|(event, json)| stream.send(event, json))
+      };
+      if result.is_err() {
+        return;
+      }
+    }
+  }
+}
+
+/* test for pri ******************************************************************************************/
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+  use std::{
+    io::{self, Write},
+    sync::{Arc, mpsc},
+    thread,
+  };
+
+  /// What a stream wrote, readable while the stream is still open.
+  #[derive(Clone, Default)]
+  struct Sink(Arc<Mutex<Vec<u8>>>);
+
+  impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.0.lock().unwrap().extend_from_slice(buf);
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  impl Sink {
+    fn stream(&self) -> EventStream {
+      EventStream::from_writer(Box::new(self.clone()))
+    }
+
+    fn text(&self) -> String {
+      String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+  }
+
+  #[test]
+  fn serve_sends_only_the_latest_values_to_late_subscribers() {
+    let hub = LiveHub::default();
+    hub.publish_latest("state", &1);
+    hub.publish_latest("session", &"a");
+    hub.publish_latest("state", &2);
+    hub.close();
+
+    let sink = Sink::default();
+    hub.serve(sink.stream());
+    assert_eq!(
+      sink.text(),
+      "event: session\ndata: \"a\"\n\nevent: state\ndata: 2\n\n"
+    );
+  }
+
+  #[test]
+  fn close_wakes_blocked_streams() {
+    let hub = Arc::new(LiveHub::default());
+    let sink = Sink::default();
+    let (done, finished) = mpsc::channel();
+    let server = {
+      let (hub, sink) = (hub.clone(), sink.clone());
+      thread::spawn(move || {
+        hub.serve(sink.stream());
+        let _ = done.send(());
+      })
+    };
+    // Published while the stream waits, and right before closing.
+    hub.publish_latest("state", &"running");
+    hub.publish_latest("state", &"stopped");
+    hub.close();
+
+    // Well within `WAKE_INTERVAL`, so the close itself woke the stream.
+    finished
+      .recv_timeout(WAKE_INTERVAL / 2)
+      .expect("stream still open after close");
+    server.join().unwrap();
+    assert!(
+      sink.text().ends_with("event: state\ndata: \"stopped\"\n\n"),
+      "{:?}",
+      sink.text()
+    );
+  }
+
+  #[test]
+  fn copy_latest_to_republishes_every_value() {
+    let (hub, lobby) = (LiveHub::default(), LiveHub::default());
+    hub.publish_latest("state", &1);
+    hub.publish_latest("session", &2);
+    hub.copy_latest_to(&lobby);
+    lobby.close();
+
+    let sink = Sink::default();
+    lobby.serve(sink.stream());
+    assert_eq!(
+      sink.text(),
+      "event: state\ndata: 1\n\nevent: session\ndata: 2\n\n"
+    );
+  }
+
+  #[test]
+  fn remote_request_parses_commands() {
+    let parse = |body: &str| serde_json::from_str::<RemoteRequest>(body).map(|req| req.command);
+    assert_eq!(
+      parse(r#"{"command":"start"}"#).unwrap(),
+      RemoteCommand::Start
+    );
+    assert_eq!(
+      parse(r#"{"command":"pause"}"#).unwrap(),
+      RemoteCommand::Pause
+    );
+    assert_eq!(
+      parse(r#"{"command":"resume"}"#).unwrap(),
+      RemoteCommand::Resume
+    );
+    assert_eq!(parse(r#"{"command":"skip"}"#).unwrap(), RemoteCommand::Skip);
+    assert_eq!(parse(r#"{"command":"stop"}"#).unwrap(), RemoteCommand::Stop);
+    assert!(parse(r#"{"command":"Stop"}"#).is_err());
+    assert!(parse(r#"{"command":"restart"}"#).is_err());
+    assert!(parse("{}").is_err());
+  }
+}
@@ -1,52 +1,87 @@
 /* use  **************************************************************************************************/
 
+use checkpoint::{Checkpoint, RunProgress};
+use chrono::{Local, NaiveDate};
 use clap::{Args, Parser, Subcommand};
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use config::Config;
 use crossterm::{
-  cursor::{Hide, MoveTo, Show},
+  cursor::{Hide, Show},
   event::{self, Event, KeyCode, KeyModifiers},
-  execute,
-  terminal::{self, Clear, ClearType},
+  execute, terminal,
 };
+use cues::{Cue, CueOutput, Cues, DEFAULT_TONE_COMMAND};
+use events::{EventLog, WorkoutEvent};
+use figure::Tremor;
+use history::{SessionConfig, SessionFilter, SessionRecord, SessionSource, SessionStatus};
+use hooks::{HookSpec, Hooks};
+use live::{LiveState, RemoteRequest};
+use plan::WorkoutPlan;
+use render::{Line, Renderer, Style};
+use service::{DEFAULT_SERVICE_NAME, ServiceScope, ServiceUnit};
+use session::{SessionWorkout, Sessions};
+use shutdown::{Shutdown, ShutdownSignal};
+use stats::Stats;
 use std::{
   env,
-  io::{self, Write},
-  sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
-  },
+  io::{self, BufRead, IsTerminal, Read, Write},
+  path::PathBuf,
+  sync::mpsc,
+  thread,
   time::{Duration, Instant},
 };
-use tiny_http::{Header, Response, Server};
+use systemd::Notifier;
+use theme::{Theme, ThemeName};
+use timeline::{DEFAULT_EXERCISE, Phase, TimelineState, WorkoutTimeline};
+use tiny_http::{Method, Request, Server};
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{EnvFilter, prelude::*};
+use web::{EventStream, Router};
+use workout::{TempoArgs, UniformWorkout, WebWorkout, WorkoutSource};
 
 /* mod  **************************************************************************************************/
 
+mod checkpoint;
+mod config;
+mod cues;
+mod digits;
+mod dirs;
+mod events;
+mod figure;
+mod history;
+mod hooks;
+mod live;
+mod plan;
+mod render;
+mod service;
+mod session;
+mod shutdown;
+mod stats;
+mod systemd;
+mod theme;
+mod timeline;
+mod web;
+mod workout;
+
 /* type alias  *******************************************************************************************/
 
 /* global const  *****************************************************************************************/
 
 const TICK_MS: u64 = 20;
-const HEADER_LINES: usize = 7;
-const FLOOR_LINES: usize = 1;
-const POSE_LINES: usize = 5;
-const POSE_COUNT: usize = 9;
+const HEADER_LINES: usize = 12;
 const DEFAULT_ROWS: usize = 24;
-const HOLD_SECS: f64 = 5.0;
-const FLOOR: &str = "==============================";
+const DEFAULT_COLS: usize = 80;
+/// `TOTAL ` in front of the progress bars and ` 100.0%` after them.
+const BAR_LABEL_COLS: usize = 6;
+const BAR_VALUE_COLS: usize = 7;
+const BAR_MIN_COLS: usize = 10;
+/// Columns between the figure and `--big-clock`.
+const BIG_CLOCK_GAP: usize = 4;
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
-const POSES: [[&str; POSE_LINES]; POSE_COUNT] = [
-  ["   O   ", "  /|\\  ", "   |   ", "  / \\  ", " /   \\ "],
-  ["   O   ", "  /|\\  ", "   |   ", "  / \\  ", " /_ _\\ "],
-  ["   O   ", "  /|\\  ", "   |   ", "  /_\\  ", " /   \\ "],
-  ["   O   ", "  /|\\  ", "  _|_  ", "  /_\\  ", " /   \\ "],
-  ["   O   ", "  /|\\  ", "  _|_  ", "  /_\\  ", " _/ \\_ "],
-  ["   O   ", "  /|\\  ", "  _|_  ", " _/_\\_ ", " _/ \\_ "],
-  ["   O   ", "  /|\\  ", " __|__ ", " _/_\\_ ", " _/ \\_ "],
-  ["   O   ", " _/|\\_ ", " __|__ ", " _/_\\_ ", " _/ \\_ "],
-  ["   O   ", " _/|\\_ ", " __|__ ", " _/_\\_ ", "__/ \\__"],
-];
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+/// Query parameter naming the client's web session.
+const SESSION_PARAM: &str = "session";
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(1);
 const SQUAT_WEB_HTML: &str = r##"<!doctype html>
 <html lang="ja">
   <head>
@@ -241,8 +276,84 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
         z-index: 3;
         pointer-events: none;
       }
+      #setup {
+        position: absolute;
+        top: 12px;
+        left: 14px;
+        z-index: 3;
+        max-width: min(92%, 420px);
+        padding: 8px 12px;
+        border-radius: 14px;
+        border: 1px solid var(--grid);
+        background: rgba(255, 255, 255, 0.92);
+        box-shadow: var(--shadow);
+        font-size: 12px;
+        letter-spacing: 0.04em;
+      }
+      #setup summary {
+        cursor: pointer;
+        font-weight: 700;
+      }
+      #setup-grid {
+        margin-top: 10px;
+        display: grid;
+        grid-template-columns: repeat(2, minmax(0, 1fr));
+        gap: 8px 12px;
+      }
+      #setup-grid label {
+        display: flex;
+        flex-direction: column;
+        gap: 4px;
+        font-weight: 600;
+      }
+      #setup input {
+        font-size: 13px;
+        padding: 4px 8px;
+        border-radius: 8px;
+        border: 1px solid var(--grid);
+        background: rgba(255, 255, 255, 0.9);
+        color: var(--ink);
+      }
+      #setup input[aria-invalid="true"] {
+        border-color: var(--accent);
+      }
+      #setup-error {
+        margin-top: 8px;
+        min-height: 1.3em;
+        color: var(--accent);
+        font-weight: 600;
+      }
+      #setup-actions {
+        display: flex;
+        justify-content: flex-end;
+        gap: 8px;
+      }
+      #setup-links {
+        margin-top: 8px;
+        font-size: 12px;
+        overflow-wrap: anywhere;
+      }
+      #setup-links a {
+        color: var(--ink);
+      }
+      #setup button {
+        font-size: 12px;
+        font-weight: 700;
+        padding: 4px 12px;
+        border-radius: 8px;
+        border: 1px solid var(--grid);
+        background: rgba(255, 255, 255, 0.9);
+        color: var(--ink);
+        cursor: pointer;
+      }
+      #setup button[type="submit"] {
+        background: var(--accent);
+        border-color: var(--accent);
+        color: #fff;
+      }
       #settings input:disabled,
-      #settings select:disabled {
+      #settings select:disabled,
+      #setup input:disabled {
         opacity: 0.5;
         cursor: not-allowed;
       }
@@ -338,7 +449,7 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
     <div id="version">v__VERSION__</div>
     <div id="app">
       <div id="info">
-        <div id="line1">Slow Squat  Set: 1/__SETS__  Rep: 1/__COUNT__</div>
+        <div id="line1">__EXERCISE__  Set: 1/__SETS__  Rep: 1/__COUNT__</div>
         <div id="line2">Phase: DOWN  Tempo: down 0.0s / hold 0.0s / up 0.0s</div>
         <div id="line4">Time left: 00:00.000</div>
         <div id="line5">Status: RUNNING</div>
@@ -384,6 +495,54 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
       </div>
       <div id="canvas-wrap">
         <canvas id="squat"></canvas>
+        <details id="setup">
+          <summary>Workout Setup</summary>
+          <form id="setup-form" novalidate>
+            <div id="setup-grid">
+              <label>
+                Duration (s)
+                <input name="duration" type="number" min="1" step="1" data-workout />
+              </label>
+              <label>
+                Count
+                <input name="count" type="number" min="1" step="1" data-workout />
+              </label>
+              <label>
+                Sets
+                <input name="sets" type="number" min="1" step="1" data-workout />
+              </label>
+              <label>
+                Interval (s)
+                <input name="interval" type="number" min="0" step="1" data-workout />
+              </label>
+              <label>
+                Hold (s)
+                <input name="hold" type="number" min="0" step="0.1" data-workout />
+              </label>
+              <label>
+                Tremor (Hz)
+                <input name="freq" type="number" min="0" step="0.1" />
+              </label>
+              <label>
+                Swing Start
+                <input name="swingStart" type="number" min="0" step="0.1" />
+              </label>
+              <label>
+                Swing Stop
+                <input name="swingStop" type="number" min="0" step="0.1" />
+              </label>
+            </div>
+            <div id="setup-error" role="alert"></div>
+            <div id="setup-actions">
+              <button id="setup-reset" type="button">Reset</button>
+              <button type="submit">Apply</button>
+            </div>
+          </form>
+          <div id="setup-links">
+            Remote: <a id="remote-link" href="/remote">/remote</a><br />
+            View: <a id="view-link" href="/view">/view</a>
+          </div>
+        </details>
         <div id="voice-warning" role="status" aria-live="polite"></div>
         <div id="load">LOAD --</div>
         <div id="fps">FPS --</div>
@@ -391,28 +550,35 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
     </div>
     <script>
       (() => {
-        const config = {
-          duration: __DURATION__,
-          count: __COUNT__,
-          sets: __SETS__,
-          interval: __INTERVAL__,
-          swingStart: __SWING_START__,
-          swingStop: __SWING_STOP__,
-          freq: __FREQ__,
-        };
-        const total = config.duration;
-        const count = config.count;
-        const sets = config.sets;
-        const interval = config.interval;
-        const repDuration = total / count;
-        const hold = __HOLD__;
-        const moveDuration = (repDuration - hold) / 2;
-        const down = moveDuration;
-        const up = moveDuration;
-        const overallTotal = total * sets + interval * (sets - 1);
-        const swingStart = config.swingStart;
-        const swingStop = config.swingStop;
-        const freq = config.freq;
+        let config = __CONFIG__;
+        let timeline = config.timeline;
+        let sets = timeline.sets.length;
+        let firstSet = timeline.sets[0];
+        let overallTotal = timeline.total;
+        let segmentCursor = 0;
+        // This browser's session on the server: reloads and other tabs resume it, while
+        // other people's browsers get their own (unless the server runs with --group).
+        const clientSession = loadClientSession();
+        const sessionQuery = `session=${clientSession}`;
+        const configEndpoint = "/api/config";
+        const stateEndpoint = `/api/state?${sessionQuery}`;
+        const eventsEndpoint = `/api/events?${sessionQuery}`;
+        const controlEndpoint = "/api/control";
+        const clockEndpoint = "/api/clock";
+        const clockSyncSamples = 5;
+        const clockResyncMs = 30000;
+        let serverOffset = null;
+        let serverSession = null;
+        let joiningSession = false;
+        const livePublishMs = 250;
+        const liveHeartbeatMs = 500;
+        let liveLastKey = "";
+        let liveLastSentAt = 0;
+        let lastRemainingMs = null;
+        let lastState = null;
+        let swingStart = config.swingStart;
+        let swingStop = config.swingStop;
+        let freq = config.freq;
         const isTouch =
           "ontouchstart" in window || (navigator.maxTouchPoints || 0) > 0;
         const supportsPointer = "PointerEvent" in window;
@@ -460,6 +626,10 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
         const fpsSelect = document.getElementById("fps-select");
         const fpsDisplay = document.getElementById("fps");
         const loadDisplay = document.getElementById("load");
+        const setupPanel = document.getElementById("setup");
+        const setupForm = document.getElementById("setup-form");
+        const setupError = document.getElementById("setup-error");
+        const setupReset = document.getElementById("setup-reset");
 
         const canvas = document.getElementById("squat");
         const ctx = canvas.getContext("2d");
@@ -531,6 +701,29 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
         const lightweightStorageKey = "squatLightweight";
         const fpsStorageKey = "squatTargetFps";
         const fpsOptions = [10, 20, 30, 40, 50, 60];
+        const workoutStorageKey = "squatWorkoutSettings";
+        const setupFields = [
+          "duration",
+          "count",
+          "sets",
+          "interval",
+          "hold",
+          "swingStart",
+          "swingStop",
+          "freq",
+        ];
+        const setupQueryKeys = {
+          duration: "duration",
+          count: "count",
+          sets: "sets",
+          interval: "interval",
+          hold: "hold",
+          swingStart: "swing-start",
+          swingStop: "swing-stop",
+          freq: "freq",
+        };
+        const maxQueryReps = 10000;
+        let baseConfig = null;
         const lightweightFps = 30;
         let selectedFps = 60;
         let lightweight = true;
@@ -777,6 +970,41 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           });
         }
         applyLightweightSettings();
+        fillSetupForm(settingsFromConfig(config));
+        if (setupForm) {
+          setupForm.addEventListener("submit", (event) => {
+            event.preventDefault();
+            applySettings(readSetupForm(), true);
+          });
+        }
+        if (setupReset) {
+          setupReset.addEventListener("click", () => {
+            try {
+              localStorage.removeItem(workoutStorageKey);
+            } catch {}
+            if (baseConfig) {
+              fillSetupForm(settingsFromConfig(baseConfig));
+              applySettings(settingsFromConfig(baseConfig), false);
+            }
+          });
+        }
+        fetch(configEndpoint)
+          .then((response) => (response.ok ? response.json() : null))
+          .then((base) => {
+            baseConfig = base;
+            if (!base || window.location.search) {
+              return;
+            }
+            let stored = null;
+            try {
+              stored = JSON.parse(localStorage.getItem(workoutStorageKey) || "null");
+            } catch {}
+            if (stored && typeof stored === "object") {
+              fillSetupForm(stored);
+              applySettings(readSetupForm(), false);
+            }
+          })
+          .catch(() => {});
         if ("speechSynthesis" in window) {
           const refreshVoices = () => {
             try {
@@ -844,6 +1072,237 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           return `${pad2(minutes)}:${pad2(seconds)}.${String(millis).padStart(3, "0")}`;
         }
 
+        function formatTempo(set) {
+          return `down ${set.down.toFixed(1)}s / hold ${set.hold.toFixed(
+            1
+          )}s / up ${set.up.toFixed(1)}s`;
+        }
+
+        function timelineAt(elapsedMs) {
+          const t = Math.max(0, elapsedMs / 1000);
+          const segments = timeline.segments;
+          if (t >= timeline.total || segments.length === 0) {
+            const lastSet = timeline.sets[timeline.sets.length - 1];
+            return {
+              done: true,
+              exercise: lastSet.exercise,
+              phase: "UP",
+              set: sets,
+              rep: lastSet.reps,
+              reps: lastSet.reps,
+              totalCompletedReps: lastSet.repsBefore + lastSet.reps,
+              completedSets: sets,
+              tempo: lastSet,
+              depth: 0,
+              phaseProgress: 1,
+              setProgress: 1,
+              restRemainingMs: 0,
+              restProgress: 1,
+              activeElapsedMs: timeline.activeTotal * 1000,
+            };
+          }
+          segmentCursor = Math.min(segmentCursor, segments.length - 1);
+          while (segmentCursor > 0 && segments[segmentCursor].start > t) {
+            segmentCursor -= 1;
+          }
+          while (
+            segmentCursor + 1 < segments.length &&
+            segments[segmentCursor + 1].start <= t
+          ) {
+            segmentCursor += 1;
+          }
+          // Rust works out what each segment shows; only interpolate within it here.
+          const segment = segments[segmentCursor];
+          const within = Math.max(0, t - segment.start);
+          const phaseProgress =
+            segment.duration > 0 ? clamp(within / segment.duration, 0, 1) : 1;
+          const lerp = ([from, to]) => from + (to - from) * phaseProgress;
+          const isRest = segment.phase === "REST";
+          return {
+            done: false,
+            exercise: timeline.sets[segment.exerciseSet].exercise,
+            phase: segment.phase,
+            set: segment.shownSet,
+            rep: segment.shownRep,
+            reps: segment.reps,
+            totalCompletedReps: segment.completedReps,
+            completedSets: segment.completedSets,
+            tempo: timeline.sets[segment.set],
+            depth: lerp(segment.depth),
+            phaseProgress,
+            setProgress: lerp(segment.setProgress),
+            restRemainingMs: isRest
+              ? Math.max(0, segment.duration - within) * 1000
+              : 0,
+            restProgress: isRest ? phaseProgress : 0,
+            activeElapsedMs: lerp(segment.active) * 1000,
+          };
+        }
+
+        function settingsFromConfig(source) {
+          const values = {
+            swingStart: source.swingStart,
+            swingStop: source.swingStop,
+            freq: source.freq,
+          };
+          if (source.mode === "uniform") {
+            values.duration = source.duration;
+            values.count = source.count;
+            values.sets = source.sets;
+            values.interval = source.interval;
+            values.hold = source.timeline.sets[0].hold;
+          }
+          return values;
+        }
+
+        function workoutEditable() {
+          return config.mode === "uniform" && !config.group;
+        }
+
+        function fillSetupForm(values) {
+          if (!setupForm) {
+            return;
+          }
+          for (const name of setupFields) {
+            const input = setupForm.elements[name];
+            if (values[name] !== undefined && values[name] !== null) {
+              input.value = String(values[name]);
+            }
+            input.disabled = !workoutEditable() && input.hasAttribute("data-workout");
+          }
+        }
+
+        function readSetupForm() {
+          const values = {};
+          for (const name of setupFields) {
+            const input = setupForm.elements[name];
+            if (!input.disabled) {
+              values[name] = input.value.trim() === "" ? NaN : Number(input.value);
+            }
+          }
+          return values;
+        }
+
+        // Mirrors the server-side checks so mistakes show up before the round trip.
+        function validateSettings(values) {
+          const invalid = [];
+          let message = null;
+          const fail = (names, text) => {
+            invalid.push(...names);
+            message = message || text;
+          };
+          if (workoutEditable()) {
+            const whole = ["duration", "count", "sets"];
+            const bad = whole.filter((name) => !Number.isInteger(values[name]) || values[name] < 1);
+            if (bad.length) {
+              fail(bad, "duration, count and sets must be >= 1");
+            }
+            if (!Number.isInteger(values.interval) || values.interval < 0) {
+              fail(["interval"], "interval must be a whole number of seconds >= 0");
+            }
+            if (!Number.isFinite(values.hold) || values.hold < 0) {
+              fail(["hold"], "hold must be >= 0s");
+            } else if (!bad.length && values.duration / values.count <= values.hold) {
+              const hold = values.hold.toFixed(1);
+              fail(
+                ["duration", "count", "hold"],
+                `duration/count must be greater than ${hold}s to allow a ${hold}s hold`
+              );
+            }
+            if (!bad.length && values.count * values.sets > maxQueryReps) {
+              fail(["count", "sets"], `count * sets must be <= ${maxQueryReps}`);
+            }
+          }
+          const tremor = ["swingStart", "swingStop", "freq"];
+          const nonFinite = tremor.filter((name) => !Number.isFinite(values[name]));
+          if (nonFinite.length) {
+            fail(nonFinite, "swing-start, swing-stop, and freq must be finite");
+          }
+          const negative = tremor.filter((name) => values[name] < 0);
+          if (negative.length) {
+            fail(negative, "swing-start, swing-stop, and freq must be >= 0");
+          }
+          for (const name of setupFields) {
+            const input = setupForm.elements[name];
+            input.setAttribute("aria-invalid", invalid.includes(name) ? "true" : "false");
+          }
+          return message;
+        }
+
+        // Only settings that differ from the server's own are sent, so an untouched hold keeps
+        // whatever --tempo/--down/--up the server was started with.
+        function settingsQuery(values) {
+          const base = settingsFromConfig(baseConfig);
+          const params = new URLSearchParams();
+          for (const name of setupFields) {
+            if (values[name] !== undefined && values[name] !== base[name]) {
+              params.set(setupQueryKeys[name], String(values[name]));
+            }
+          }
+          return params.toString();
+        }
+
+        function showSetupError(message) {
+          if (setupError) {
+            setupError.textContent = message || "";
+          }
+          if (message && setupPanel) {
+            setupPanel.open = true;
+          }
+        }
+
+        async function applySettings(values, persist) {
+          if (countdownStarted || joiningSession) {
+            return;
+          }
+          const invalid = validateSettings(values);
+          if (invalid) {
+            showSetupError(invalid);
+            return;
+          }
+          if (!baseConfig) {
+            showSetupError("server settings are not loaded yet");
+            return;
+          }
+          const query = settingsQuery(values);
+          let next = null;
+          try {
+            const response = await fetch(query ? `${configEndpoint}?${query}` : configEndpoint);
+            next = await response.json();
+            if (!response.ok) {
+              showSetupError(next.error || `HTTP ${response.status}`);
+              return;
+            }
+          } catch (err) {
+            showSetupError(String(err));
+            return;
+          }
+          if (countdownStarted || joiningSession) {
+            return;
+          }
+          applyConfig(next);
+          history.replaceState(null, "", query ? `?${query}` : window.location.pathname);
+          if (persist) {
+            try {
+              localStorage.setItem(workoutStorageKey, JSON.stringify(values));
+            } catch {}
+          }
+          showSetupError("");
+        }
+
+        function applyConfig(next) {
+          config = next;
+          timeline = next.timeline;
+          sets = timeline.sets.length;
+          firstSet = timeline.sets[0];
+          overallTotal = timeline.total;
+          segmentCursor = 0;
+          swingStart = next.swingStart;
+          swingStop = next.swingStop;
+          freq = next.freq;
+          fillSetupForm(settingsFromConfig(next));
+        }
+
         function normalizeFps(value) {
           const num = Number(value);
           if (!Number.isFinite(num)) {
@@ -1637,10 +2096,8 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
             completionAt = null;
             tremorFade = 1;
             lastRenderStamp = 0;
-            line1.textContent = `Slow Squat  Set: 1/${sets}  Rep: 1/${count}`;
-            line2.textContent = `Phase: DOWN  Tempo: down ${down.toFixed(
-              1
-            )}s / hold ${hold.toFixed(1)}s / up ${up.toFixed(1)}s`;
+            line1.textContent = `${firstSet.exercise}  Set: 1/${sets}  Rep: 1/${firstSet.reps}`;
+            line2.textContent = `Phase: DOWN  Tempo: ${formatTempo(firstSet)}`;
             lastTimeLeft = formatTimeLeft(overallTotal * 1000);
             line4.textContent = `Time left: ${lastTimeLeft}`;
             if (!countdownStarted) {
@@ -1672,7 +2129,7 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
 
             if (elapsedCountdown >= countdownSeconds * 1000) {
               started = true;
-              animationStart = performance.now();
+              animationStart = countdownStart + countdownSeconds * 1000;
               paused = false;
               pauseStarted = null;
               pausedTotal = 0;
@@ -1694,46 +2151,19 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           tremorTime = effectiveNow;
           const elapsed = Math.max(0, effectiveNow - animationStart - pausedTotal);
           const overallMs = overallTotal * 1000;
-          const done = elapsed >= overallMs;
-          let phase = "DOWN";
-          let depth = 0;
+          const state = timelineAt(elapsed);
+          lastState = state;
+          const done = state.done;
+          const phase = state.phase;
+          const isRest = phase === "REST";
+          const restRemainingMs = state.restRemainingMs;
+          const activeElapsedMs = state.activeElapsedMs;
           let moveProgress = lastMoveProgress;
           let holdProgress = lastHoldProgress;
-          let restRemainingMs = 0;
-          let activeElapsedMs = 0;
-          let setIndex = 0;
-          let withinSetMs = 0;
-          let isRest = false;
-          let completed = 0;
-
-          if (!done) {
-            const setMs = total * 1000;
-            const restMs = interval * 1000;
-            const cycleMs = setMs + restMs;
-            setIndex = Math.floor(elapsed / cycleMs);
-            if (setIndex >= sets) {
-              setIndex = sets - 1;
-            }
-            const withinCycle = elapsed - setIndex * cycleMs;
-            if (setIndex < sets - 1 && withinCycle >= setMs) {
-              isRest = true;
-              restRemainingMs = Math.max(0, restMs - (withinCycle - setMs));
-            } else {
-              withinSetMs = withinCycle;
-            }
-            const completedCycles = Math.floor(elapsed / cycleMs);
-            let restElapsedMs = completedCycles * restMs;
-            if (withinCycle > setMs) {
-              restElapsedMs += withinCycle - setMs;
-            }
-            activeElapsedMs = Math.max(0, elapsed - restElapsedMs);
-          } else {
-            activeElapsedMs = total * 1000 * sets;
-          }
 
           const enteringRest = isRest && !wasRest;
           const leavingRest = !isRest && wasRest;
-          if (enteringRest && interval > 0) {
+          if (enteringRest) {
             triggerCalloutMessage("INTERVAL START", voicePhraseFor("INTERVAL_START"), effectiveNow);
             lastRestCountdownSpoken = null;
           }
@@ -1742,73 +2172,41 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           }
 
           if (done) {
-            phase = "UP";
-            depth = 0;
             moveProgress = 100;
-            completed = count;
             lastSetProgress = 100;
             restActive = false;
             lastRestProgress = 100;
           } else if (isRest) {
-            phase = "REST";
-            depth = 0;
             moveProgress = 0;
             holdProgress = 0;
-            completed = 0;
             lastSetProgress = 100;
             restActive = true;
-            if (interval > 0) {
-              lastRestProgress = Math.max(
-                0,
-                Math.min(100, ((interval * 1000 - restRemainingMs) / (interval * 1000)) * 100)
-              );
-            } else {
-              lastRestProgress = 100;
-            }
+            lastRestProgress = clamp(state.restProgress * 100, 0, 100);
           } else {
             restActive = false;
             lastRestProgress = 0;
-            const withinSetSec = withinSetMs / 1000;
-            completed = Math.min(Math.floor(withinSetSec / repDuration), count);
-            const within = withinSetSec - completed * repDuration;
-            if (within < down) {
-              phase = "DOWN";
-              const t = down > 0 ? within / down : 1;
-              depth = t;
-              moveProgress = t * 100;
-            } else if (within < down + hold) {
-              phase = "HOLD";
-              depth = 1;
-              const t = hold > 0 ? (within - down) / hold : 1;
-              holdProgress = t * 100;
+            if (phase === "HOLD") {
+              holdProgress = state.phaseProgress * 100;
               moveProgress = 100;
             } else {
-              phase = "UP";
-              const t = up > 0 ? (within - down - hold) / up : 1;
-              depth = 1 - t;
-              moveProgress = t * 100;
+              moveProgress = state.phaseProgress * 100;
             }
-            lastSetProgress = Math.max(0, Math.min(100, (withinSetSec / total) * 100));
+            lastSetProgress = clamp(state.setProgress * 100, 0, 100);
           }
 
-          const clamped = Math.max(0, Math.min(1, depth));
+          const clamped = Math.max(0, Math.min(1, state.depth));
           currentProgress = clamped;
           lastMoveProgress = Math.max(0, Math.min(100, moveProgress));
           if (phase === "HOLD") {
             lastHoldProgress = Math.max(0, Math.min(100, holdProgress));
           }
           const remaining = Math.max(0, overallMs - elapsed);
+          lastRemainingMs = remaining;
           lastOverallProgress = Math.max(0, Math.min(100, (elapsed / overallMs) * 100));
           lastTimeLeft = formatTimeLeft(remaining);
-          const displaySet = done
-            ? sets
-            : isRest && setIndex < sets - 1
-              ? setIndex + 2
-              : setIndex + 1;
-          const current = isRest ? 0 : Math.min(completed + 1, count);
           let restCountdownValue = null;
           let restCountdownActive = false;
-          if (isRest && interval > 0) {
+          if (isRest) {
             const countdownWindowMs = countdownSeconds * 1000;
             if (restRemainingMs > 0 && restRemainingMs <= countdownWindowMs) {
               restCountdownActive = true;
@@ -1887,10 +2285,8 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
             }
           }
 
-          line1.textContent = `Slow Squat  Set: ${displaySet}/${sets}  Rep: ${done ? count : current}/${count}`;
-          line2.textContent = `Phase: ${phase}  Tempo: down ${down.toFixed(
-            1
-          )}s / hold ${hold.toFixed(1)}s / up ${up.toFixed(1)}s`;
+          line1.textContent = `${state.exercise}  Set: ${state.set}/${sets}  Rep: ${state.rep}/${state.reps}`;
+          line2.textContent = `Phase: ${phase}  Tempo: ${formatTempo(state.tempo)}`;
           line4.textContent = `Time left: ${lastTimeLeft}`;
           if (paused) {
             line5.textContent = "Status: PAUSED";
@@ -1934,6 +2330,55 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           recordLoad(updateStart, performance.now());
         }
 
+        function liveSnapshot() {
+          const state = lastState || timelineAt(0);
+          let status = "running";
+          if (stopped) {
+            status = "stopped";
+          } else if (!countdownStarted) {
+            status = "waiting";
+          } else if (!started) {
+            status = "countdown";
+          } else if (state.done) {
+            status = "complete";
+          } else if (paused) {
+            status = "paused";
+          } else if (state.phase === "REST") {
+            status = "rest";
+          }
+          return {
+            status,
+            exercise: state.exercise,
+            phase: state.phase,
+            set: state.set,
+            sets,
+            rep: state.rep,
+            reps: state.reps,
+            timeLeftMs: started && lastRemainingMs !== null ? lastRemainingMs : overallTotal * 1000,
+            restLeftMs: state.restRemainingMs,
+            countdown: status === "countdown" ? lastCountdownSpoken : null,
+          };
+        }
+
+        // Viewers on /view follow along through the server; only changes and a
+        // slow heartbeat for the running clocks are sent.
+        function publishLiveState() {
+          const snapshot = liveSnapshot();
+          const key = JSON.stringify({ ...snapshot, timeLeftMs: 0, restLeftMs: 0 });
+          const now = performance.now();
+          const ticking = snapshot.status === "running" || snapshot.status === "rest";
+          if (key === liveLastKey && !(ticking && now - liveLastSentAt >= liveHeartbeatMs)) {
+            return;
+          }
+          liveLastKey = key;
+          liveLastSentAt = now;
+          fetch(stateEndpoint, {
+            method: "POST",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify(snapshot),
+          }).catch(() => {});
+        }
+
         function speakText(text, meta = null) {
           if (!voiceEnabled) {
             logVoiceStatus("発声スキップ: Voice OFF", { text, meta });
@@ -2129,23 +2574,6 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           triggerCalloutMessage(text, voicePhraseFor(phase), now);
         }
 
-        function togglePause() {
-          if (stopped || !started) {
-            return;
-          }
-          if (paused) {
-            if (pauseStarted !== null) {
-              pausedTotal += performance.now() - pauseStarted;
-            }
-            paused = false;
-            pauseStarted = null;
-            requestAnimationFrame(update);
-          } else {
-            paused = true;
-            pauseStarted = performance.now();
-          }
-        }
-
         function stop() {
           if (stopped) {
             return;
@@ -2184,61 +2612,29 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           }
         });
 
-        function startCountdown() {
-          if (started || countdownStarted) {
-            return;
-          }
-          enableKeepAwake();
-          unlockSpeech();
-          ensureBeepAudio();
-          countdownStarted = true;
-          countdownStart = performance.now();
-          requestAnimationFrame(update);
-        }
-
-        function skipCountdown() {
-          if (started) {
+        window.addEventListener("keydown", (event) => {
+          if (setupPanel && setupPanel.contains(event.target)) {
             return;
           }
-          enableKeepAwake();
-          unlockSpeech();
-          ensureBeepAudio();
-          countdownStarted = true;
-          started = true;
-          animationStart = performance.now();
-          paused = false;
-          pauseStarted = null;
-          pausedTotal = 0;
-          if (beepStartActiveMs === null) {
-            beepStartActiveMs = 0;
-            lastBeepIndex = 0;
-            if (voiceEnabled) {
-              playBeep();
-            }
-          }
-          requestAnimationFrame(update);
-        }
-
-        window.addEventListener("keydown", (event) => {
           if (event.code === "Enter") {
-            if (!countdownStarted) {
-              startCountdown();
-            } else if (!started) {
-              skipCountdown();
+            if (!started) {
+              control(countdownStarted ? "skip" : "start");
             }
             return;
           }
           if (event.code === "Space") {
             event.preventDefault();
-            togglePause();
+            if (started) {
+              control(paused ? "resume" : "pause");
+            }
             return;
           }
           if (event.code === "Escape") {
-            stop();
+            control("stop");
             return;
           }
           if ((event.ctrlKey || event.metaKey) && (event.key === "c" || event.key === "C")) {
-            stop();
+            control("stop");
           }
         });
 
@@ -2248,14 +2644,10 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
           }
           event.preventDefault();
           if (!started) {
-            if (!countdownStarted) {
-              startCountdown();
-            } else {
-              skipCountdown();
-            }
+            control(countdownStarted ? "skip" : "start");
             return;
           }
-          togglePause();
+          control(paused ? "resume" : "pause");
         });
 
         canvas.addEventListener(
@@ -2266,60 +2658,558 @@ const SQUAT_WEB_HTML: &str = r##"<!doctype html>
             }
             event.preventDefault();
             if (!started) {
-              if (!countdownStarted) {
-                startCountdown();
-              } else {
-                skipCountdown();
-              }
+              control(countdownStarted ? "skip" : "start");
               return;
             }
-            togglePause();
+            control(paused ? "resume" : "pause");
           },
           { passive: false }
         );
 
+        // The server owns the session; local input only asks for changes and every page,
+        // this one included, follows the `session` events that come back.
+        function control(command) {
+          if (command === "start" || command === "skip") {
+            unlockSpeech();
+            ensureBeepAudio();
+          }
+          const search = window.location.search;
+          fetch(`${controlEndpoint}${search ? `${search}&` : "?"}${sessionQuery}`, {
+            method: "POST",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ command }),
+          }).catch(() => {});
+        }
+
+        function loadClientSession() {
+          const storageKey = "squatSessionId";
+          const fresh = () =>
+            "randomUUID" in crypto
+              ? crypto.randomUUID()
+              : Array.from(crypto.getRandomValues(new Uint8Array(16)), (byte) =>
+                  byte.toString(16).padStart(2, "0")
+                ).join("");
+          try {
+            let id = localStorage.getItem(storageKey);
+            if (!id || !/^[A-Za-z0-9_-]{1,64}$/.test(id)) {
+              id = fresh();
+              localStorage.setItem(storageKey, id);
+            }
+            return id;
+          } catch {
+            return fresh();
+          }
+        }
+
+        for (const [linkId, path] of [
+          ["remote-link", "/remote"],
+          ["view-link", "/view"],
+        ]) {
+          const link = document.getElementById(linkId);
+          if (link) {
+            link.href = `${path}?${sessionQuery}`;
+            link.textContent = new URL(link.href, window.location.href).href;
+          }
+        }
+
+        // Keeps the sample with the shortest round trip; half of it is the one-way delay.
+        async function syncClock() {
+          let best = null;
+          for (let i = 0; i < clockSyncSamples; i += 1) {
+            try {
+              const sentAt = performance.now();
+              const response = await fetch(clockEndpoint, { cache: "no-store" });
+              const body = await response.json();
+              const receivedAt = performance.now();
+              const rtt = receivedAt - sentAt;
+              if (!best || rtt < best.rtt) {
+                best = { rtt, offset: body.now + rtt / 2 - receivedAt };
+              }
+            } catch {}
+          }
+          if (best) {
+            serverOffset = best.offset;
+            if (serverSession) {
+              applySession(serverSession);
+            }
+          }
+        }
+
+        function sessionActive(session) {
+          if (session.startedAt === null || session.stopped) {
+            return false;
+          }
+          const now = performance.now() + serverOffset;
+          const pausedFor = session.pausedAt !== null ? now - session.pausedAt : 0;
+          return now - session.startedAt - session.pausedMs - pausedFor < session.totalMs;
+        }
+
+        function applySession(session) {
+          serverSession = session;
+          if (serverOffset === null || session.startedAt === null) {
+            return;
+          }
+          if (countdownStarted) {
+            if (session.stopped) {
+              if (!stopped) {
+                stop();
+              }
+            } else if (stopped || (lastState && lastState.done)) {
+              if (sessionActive(session)) {
+                // A new round started; join it with a clean page.
+                window.location.reload();
+              }
+            } else {
+              followSession(session);
+            }
+            return;
+          }
+          if (!sessionActive(session) || joiningSession) {
+            return;
+          }
+          const query = window.location.search.replace(/^\?/, "");
+          if (session.query === query) {
+            followSession(session);
+            return;
+          }
+          // Another tab of this session started a different workout; load it before joining.
+          joiningSession = true;
+          fetch(session.query ? `${configEndpoint}?${session.query}` : configEndpoint)
+            .then((response) => (response.ok ? response.json() : Promise.reject(response.status)))
+            .then((next) => {
+              applyConfig(next);
+              history.replaceState(
+                null,
+                "",
+                session.query ? `?${session.query}` : window.location.pathname
+              );
+            })
+            .catch(() => {})
+            .finally(() => {
+              joiningSession = false;
+              applySession(serverSession);
+            });
+        }
+
+        function followSession(session) {
+          const now = performance.now();
+          const startLocal = session.startedAt - serverOffset;
+          if (!countdownStarted) {
+            if (setupPanel) {
+              setupPanel.hidden = true;
+            }
+            enableKeepAwake();
+            countdownStarted = true;
+          }
+          countdownStart = startLocal - countdownSeconds * 1000;
+          if (!started && now >= startLocal) {
+            started = true;
+            beepStartActiveMs = 0;
+            lastBeepIndex = 0;
+            if (voiceEnabled && now - startLocal < beepIntervalMs) {
+              playBeep();
+            }
+          }
+          animationStart = startLocal;
+          pausedTotal = session.pausedMs;
+          paused = session.pausedAt !== null;
+          pauseStarted = paused ? session.pausedAt - serverOffset : null;
+        }
+
+        if ("EventSource" in window) {
+          const serverEvents = new EventSource(eventsEndpoint);
+          serverEvents.addEventListener("session", (event) => {
+            try {
+              applySession(JSON.parse(event.data));
+            } catch {}
+          });
+        }
+        syncClock();
+        setInterval(syncClock, clockResyncMs);
+
         resize();
         requestAnimationFrame(update);
+        setInterval(publishLiveState, livePublishMs);
       })();
     </script>
   </body>
 </html>
 "##;
 
-/* trait  ************************************************************************************************/
-
-/* enum  *************************************************************************************************/
-
-#[derive(Subcommand, Debug)]
-enum Commands {
-  Squat(SquatArgs),
-  SquatWeb(SquatWebArgs),
-}
-
-#[derive(Debug)]
-enum InputAction {
-  None,
-  TogglePause,
-  Exit,
-}
-
-/* struct  ***********************************************************************************************/
-
-#[derive(Parser, Debug)]
-#[command(name = "trainer", version, about = "CLI training utilities")]
-struct Cli {
+/// Read-only view of whatever workout a `squat-web` page is running, fed by `/api/events`.
+const SQUAT_VIEW_HTML: &str = r##"<!doctype html>
+<html lang="ja">
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <title>Slow Squat Viewer</title>
+    <style>
+      :root {
+        --bg: #f5f0e6;
+        --ink: #1d1c1a;
+        --accent: #c24a3a;
+        --accent-2: #2f6f6d;
+        --grid: #e1d6c4;
+      }
+      * {
+        box-sizing: border-box;
+      }
+      body {
+        margin: 0;
+        min-height: 100vh;
+        display: flex;
+        flex-direction: column;
+        align-items: center;
+        justify-content: center;
+        gap: 2vh;
+        padding: 4vh 4vw;
+        font-family: "Hiragino Sans", "Avenir Next", "Yu Gothic", "YuGothic",
+          "Helvetica Neue", sans-serif;
+        background: var(--bg);
+        color: var(--ink);
+        text-align: center;
+      }
+      body[data-phase="DOWN"] #phase {
+        color: var(--accent);
+      }
+      body[data-phase="HOLD"] #phase {
+        color: var(--ink);
+      }
+      body[data-phase="UP"] #phase,
+      body[data-phase="REST"] #phase {
+        color: var(--accent-2);
+      }
+      #exercise {
+        font-size: clamp(18px, 4vw, 40px);
+        font-weight: 700;
+        letter-spacing: 0.06em;
+        text-transform: uppercase;
+      }
+      #phase {
+        font-size: clamp(64px, 22vw, 280px);
+        font-weight: 800;
+        line-height: 1;
+      }
+      #counts {
+        font-size: clamp(28px, 8vw, 96px);
+        font-weight: 700;
+        font-variant-numeric: tabular-nums;
+      }
+      #time-left {
+        font-size: clamp(22px, 6vw, 72px);
+        font-family: "SF Mono", "Menlo", "Consolas", monospace;
+      }
+      #status {
+        font-size: clamp(14px, 2.4vw, 24px);
+        letter-spacing: 0.08em;
+        opacity: 0.7;
+      }
+    </style>
+  </head>
+  <body>
+    <div id="exercise">Slow Squat</div>
+    <div id="phase">--</div>
+    <div id="counts">Set -/-  Rep -/-</div>
+    <div id="time-left">--:--</div>
+    <div id="status">CONNECTING</div>
+    <script>
+      (() => {
+        const exercise = document.getElementById("exercise");
+        const phase = document.getElementById("phase");
+        const counts = document.getElementById("counts");
+        const timeLeft = document.getElementById("time-left");
+        const status = document.getElementById("status");
+        // `?session=` picks the workout to follow; without it, the one controlled last.
+        const session = new URLSearchParams(window.location.search).get("session");
+        const sessionQuery = session ? `?session=${encodeURIComponent(session)}` : "";
+
+        function pad2(value) {
+          return String(value).padStart(2, "0");
+        }
+
+        function formatClock(ms) {
+          const totalSec = Math.ceil(Math.max(0, ms) / 1000);
+          return `${pad2(Math.floor(totalSec / 60))}:${pad2(totalSec % 60)}`;
+        }
+
+        function render(state) {
+          exercise.textContent = state.exercise;
+          document.body.dataset.phase = state.phase;
+          if (state.status === "countdown" && state.countdown !== null) {
+            phase.textContent = String(state.countdown);
+          } else if (state.status === "waiting") {
+            phase.textContent = "READY";
+          } else if (state.status === "complete") {
+            phase.textContent = "DONE";
+          } else if (state.status === "rest") {
+            phase.textContent = formatClock(state.restLeftMs);
+          } else {
+            phase.textContent = state.phase;
+          }
+          counts.textContent = `Set ${state.set}/${state.sets}  Rep ${state.rep}/${state.reps}`;
+          timeLeft.textContent = formatClock(state.timeLeftMs);
+          status.textContent = state.status.toUpperCase();
+        }
+
+        const events = new EventSource(`/api/events${sessionQuery}`);
+        events.addEventListener("open", () => {
+          if (status.textContent === "CONNECTING" || status.textContent === "OFFLINE") {
+            status.textContent = "WAITING FOR A WORKOUT";
+          }
+        });
+        events.addEventListener("state", (event) => {
+          try {
+            render(JSON.parse(event.data));
+          } catch {}
+        });
+        events.addEventListener("error", () => {
+          status.textContent = "OFFLINE";
+        });
+      })();
+    </script>
+  </body>
+</html>
+"##;
+
+/// Phone remote for a running `squat-web` page; commands go through `/api/control`.
+const SQUAT_REMOTE_HTML: &str = r##"<!doctype html>
+<html lang="ja">
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <title>Slow Squat Remote</title>
+    <style>
+      :root {
+        --bg: #f5f0e6;
+        --ink: #1d1c1a;
+        --accent: #c24a3a;
+        --accent-2: #2f6f6d;
+        --grid: #e1d6c4;
+      }
+      * {
+        box-sizing: border-box;
+      }
+      body {
+        margin: 0;
+        min-height: 100vh;
+        display: flex;
+        flex-direction: column;
+        gap: 16px;
+        padding: 20px;
+        font-family: "Hiragino Sans", "Avenir Next", "Yu Gothic", "YuGothic",
+          "Helvetica Neue", sans-serif;
+        background: var(--bg);
+        color: var(--ink);
+        -webkit-user-select: none;
+        user-select: none;
+      }
+      #status {
+        text-align: center;
+        line-height: 1.5;
+      }
+      #phase {
+        font-size: 48px;
+        font-weight: 800;
+      }
+      #counts,
+      #time-left {
+        font-size: 20px;
+        font-variant-numeric: tabular-nums;
+      }
+      #buttons {
+        flex: 1;
+        display: grid;
+        grid-template-columns: 1fr 1fr;
+        gap: 12px;
+      }
+      button {
+        min-height: 96px;
+        font-size: 24px;
+        font-weight: 700;
+        border-radius: 18px;
+        border: 1px solid var(--grid);
+        background: rgba(255, 255, 255, 0.9);
+        color: var(--ink);
+        touch-action: manipulation;
+      }
+      button:active {
+        transform: scale(0.98);
+      }
+      button[data-command="pause"],
+      button[data-command="resume"] {
+        grid-column: span 2;
+        min-height: 160px;
+        background: var(--accent-2);
+        border-color: var(--accent-2);
+        color: #fff;
+      }
+      button[data-command="stop"] {
+        background: var(--accent);
+        border-color: var(--accent);
+        color: #fff;
+      }
+      #error {
+        min-height: 1.3em;
+        text-align: center;
+        color: var(--accent);
+        font-weight: 600;
+      }
+    </style>
+  </head>
+  <body>
+    <div id="status">
+      <div id="phase">--</div>
+      <div id="counts">Set -/-  Rep -/-</div>
+      <div id="time-left">--:--</div>
+    </div>
+    <div id="buttons">
+      <button data-command="pause" type="button">Pause</button>
+      <button data-command="resume" type="button" hidden>Resume</button>
+      <button data-command="start" type="button">Start</button>
+      <button data-command="skip" type="button">Skip</button>
+      <button data-command="stop" type="button">Stop</button>
+    </div>
+    <div id="error" role="alert"></div>
+    <script>
+      (() => {
+        const phase = document.getElementById("phase");
+        const counts = document.getElementById("counts");
+        const timeLeft = document.getElementById("time-left");
+        const error = document.getElementById("error");
+        const pauseButton = document.querySelector('[data-command="pause"]');
+        const resumeButton = document.querySelector('[data-command="resume"]');
+        // `?session=` picks the workout to control; without it, the one controlled last.
+        const session = new URLSearchParams(window.location.search).get("session");
+        const sessionQuery = session ? `?session=${encodeURIComponent(session)}` : "";
+
+        function pad2(value) {
+          return String(value).padStart(2, "0");
+        }
+
+        function formatClock(ms) {
+          const totalSec = Math.ceil(Math.max(0, ms) / 1000);
+          return `${pad2(Math.floor(totalSec / 60))}:${pad2(totalSec % 60)}`;
+        }
+
+        function send(command) {
+          fetch(`/api/control${sessionQuery}`, {
+            method: "POST",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ command }),
+          })
+            .then((response) => {
+              error.textContent = response.ok ? "" : `HTTP ${response.status}`;
+            })
+            .catch((err) => {
+              error.textContent = String(err);
+            });
+        }
+
+        for (const button of document.querySelectorAll("button[data-command]")) {
+          button.addEventListener("click", () => send(button.dataset.command));
+        }
+
+        const events = new EventSource(`/api/events${sessionQuery}`);
+        events.addEventListener("state", (event) => {
+          let state = null;
+          try {
+            state = JSON.parse(event.data);
+          } catch {
+            return;
+          }
+          phase.textContent =
+            state.status === "running" || state.status === "rest"
+              ? state.phase
+              : state.status.toUpperCase();
+          counts.textContent = `Set ${state.set}/${state.sets}  Rep ${state.rep}/${state.reps}`;
+          timeLeft.textContent = formatClock(state.timeLeftMs);
+          pauseButton.hidden = state.status === "paused";
+          resumeButton.hidden = state.status !== "paused";
+        });
+        events.addEventListener("error", () => {
+          phase.textContent = "OFFLINE";
+        });
+      })();
+    </script>
+  </body>
+</html>
+"##;
+
+/* trait  ************************************************************************************************/
+
+/* enum  *************************************************************************************************/
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+  Squat(SquatArgs),
+  SquatWeb(SquatWebArgs),
+  /// Play a workout plan file in the terminal or the browser
+  Run(RunArgs),
+  /// List recorded sessions
+  History(HistoryArgs),
+  /// Show volume, time under tension, streaks and completion rate
+  Stats(StatsArgs),
+  /// Run the web trainer as a systemd service
+  Service(ServiceArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum ServiceAction {
+  /// Write the unit, then enable and start it
+  Install(ServiceUnitArgs),
+  /// Stop and disable the service and remove its unit
+  Uninstall(ServiceTargetArgs),
+  /// Print the unit without installing it
+  Print(ServiceUnitArgs),
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+enum InputAction {
+  #[default]
+  None,
+  TogglePause,
+  Pause,
+  Resume,
+  Exit,
+}
+
+/// Where the terminal frontend draws and reads its commands.
+enum Console {
+  /// Full-screen animation in raw mode, keys from the keyboard.
+  Screen(ScreenConsole),
+  /// One line per change and commands from stdin lines, for pipes, CI logs, serial
+  /// consoles and screen readers.
+  Plain(PlainConsole),
+}
+
+/* struct  ***********************************************************************************************/
+
+#[derive(Parser, Debug)]
+#[command(name = "trainer", version, about = "CLI training utilities")]
+struct Cli {
   #[command(subcommand)]
   command: Commands,
 }
 
 #[derive(Args, Debug)]
 struct SquatArgs {
+  /// Continue the last unfinished terminal workout from its last completed rep
+  #[arg(long, conflicts_with_all = ["duration", "count", "sets", "interval", "hold", "down", "up", "tempo"])]
+  resume: bool,
   #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
   duration: u64,
   #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
   count: u32,
-  #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u64).range(0..))]
-  countdown: u64,
+  #[arg(long = "sets", alias = "set", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+  sets: u32,
+  #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u64).range(0..))]
+  interval: u64,
+  #[command(flatten)]
+  tempo: TempoArgs,
+  #[command(flatten)]
+  tremor: TremorArgs,
+  #[command(flatten)]
+  terminal: TerminalArgs,
 }
 
 #[derive(Args, Debug)]
@@ -2332,33 +3222,233 @@ struct SquatWebArgs {
   sets: u32,
   #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u64).range(0..))]
   interval: u64,
+  #[command(flatten)]
+  tempo: TempoArgs,
+  #[command(flatten)]
+  web: WebArgs,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+  /// Workout plan file (TOML)
+  #[arg(long)]
+  plan: PathBuf,
+  #[command(flatten)]
+  terminal: TerminalArgs,
+  /// Serve the plan to the browser instead of playing it in the terminal
+  #[arg(long)]
+  web: bool,
+  #[command(flatten)]
+  web_args: WebArgs,
+}
+
+// How the terminal frontend runs; shared by `squat` and `run`.
+#[derive(Args, Debug)]
+struct TerminalArgs {
+  #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u64).range(0..))]
+  countdown: u64,
+  /// One line per phase instead of a full-screen animation; commands come from stdin
+  /// lines. On by default when stdout is not a terminal
+  #[arg(long)]
+  plain: bool,
+  /// Append workout events as JSON Lines to this file, or to stdout with `-` (the
+  /// plain-mode text then goes to stderr)
+  #[arg(long, value_name = "PATH|-")]
+  events: Option<String>,
+  /// Run a shell command on an event, e.g. `--hook on_phase=HOLD 'paplay hold.oga'`;
+  /// repeatable, and added to the `[hooks]` in config.toml
+  #[arg(long, num_args = 2, value_names = ["EVENT", "COMMAND"])]
+  hook: Vec<String>,
+  /// Sound cues for phases and the countdown: the terminal bell, counted out per cue, or
+  /// tones played through --tone-command with a metronome tick every second
+  #[arg(long, value_enum, value_name = "OUTPUT")]
+  cues: Option<CueOutput>,
+  /// Cue pattern for an event as HZ:MS notes, e.g. `--cue hold=988:80,0:60,988:80` or
+  /// `--cue tick=off`; repeatable, and over the `[cues]` in config.toml
+  #[arg(long, value_name = "EVENT=PATTERN")]
+  cue: Vec<String>,
+  /// Command that plays raw signed 16-bit mono 22050 Hz audio from stdin, once per cue
+  #[arg(long, value_name = "COMMAND", default_value = DEFAULT_TONE_COMMAND)]
+  tone_command: String,
+  /// Colors of the full-screen UI [default: paper, or mono when NO_COLOR is set]
+  #[arg(long, value_enum)]
+  theme: Option<ThemeName>,
+  /// Show the time left as a big clock next to the figure
+  #[arg(long)]
+  big_clock: bool,
+}
+
+#[derive(Args, Debug)]
+struct FilterArgs {
+  /// Only sessions started on or after this date (YYYY-MM-DD)
+  #[arg(long, value_parser = clap::value_parser!(NaiveDate))]
+  since: Option<NaiveDate>,
+  /// Only sessions started on or before this date (YYYY-MM-DD)
+  #[arg(long, value_parser = clap::value_parser!(NaiveDate))]
+  until: Option<NaiveDate>,
+  /// Only sessions whose exercise contains this text (case-insensitive)
+  #[arg(long)]
+  exercise: Option<String>,
+  /// Only completed sessions
+  #[arg(long)]
+  completed: bool,
+}
+
+#[derive(Args, Debug)]
+struct HistoryArgs {
+  #[command(flatten)]
+  filter: FilterArgs,
+  /// Show only the most recent N sessions
+  #[arg(long)]
+  limit: Option<usize>,
+  /// Print JSON instead of a table
+  #[arg(long)]
+  json: bool,
+}
+
+#[derive(Args, Debug)]
+struct StatsArgs {
+  #[command(flatten)]
+  filter: FilterArgs,
+  /// Number of most recent weeks in the weekly volume table
+  #[arg(long, default_value_t = 8)]
+  weeks: usize,
+  /// Print JSON instead of a table
+  #[arg(long)]
+  json: bool,
+}
+
+#[derive(Args, Debug)]
+struct ServiceArgs {
+  #[command(subcommand)]
+  action: ServiceAction,
+}
+
+#[derive(Args, Debug)]
+struct ServiceTargetArgs {
+  #[arg(long, value_enum, default_value_t = ServiceScope::User)]
+  scope: ServiceScope,
+  /// Unit name without `.service`
+  #[arg(long, default_value = DEFAULT_SERVICE_NAME)]
+  name: String,
+  /// Print what would be written and run instead of doing it
+  #[arg(long)]
+  dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+struct ServiceUnitArgs {
+  #[command(flatten)]
+  target: ServiceTargetArgs,
+  /// trainer binary for ExecStart (default: this executable)
+  #[arg(long)]
+  bin: Option<PathBuf>,
+  /// User the system service runs as (default: the sudo user or the current user)
+  #[arg(long)]
+  run_as: Option<String>,
+  /// Group the system service runs as (default: the user's primary group)
+  #[arg(long)]
+  run_group: Option<String>,
+  /// Working directory of the service, e.g. where relative `--plan` paths live
+  #[arg(long)]
+  workdir: Option<PathBuf>,
+  /// Leave out the sandboxing options
+  #[arg(long)]
+  no_hardening: bool,
+  /// Also write a .socket unit on the command's --addr so systemd starts the service on
+  /// the first connection
+  #[arg(long)]
+  socket: bool,
+  /// Command the service runs, e.g. `squat-web --addr 0.0.0.0:12002` [default: squat-web]
+  #[arg(
+    trailing_var_arg = true,
+    allow_hyphen_values = true,
+    value_name = "COMMAND"
+  )]
+  command: Vec<String>,
+}
+
+/// The figure's fatigue tremor; shared by the terminal and the web page.
+#[derive(Args, Debug)]
+struct TremorArgs {
   #[arg(long, default_value_t = 0.4, value_parser = clap::value_parser!(f64))]
   swing_start: f64,
   #[arg(long, default_value_t = 3.4, value_parser = clap::value_parser!(f64))]
   swing_stop: f64,
   #[arg(long, default_value_t = 10.0, value_parser = clap::value_parser!(f64))]
   freq: f64,
+}
+
+#[derive(Args, Debug)]
+struct WebArgs {
+  #[command(flatten)]
+  tremor: TremorArgs,
   #[arg(long, default_value = "127.0.0.1:12002")]
   addr: String,
+  /// Group class: every page shares one session running this workout; query and
+  /// setup-panel changes are refused
+  #[arg(long)]
+  group: bool,
 }
 
-struct FrameState<'a> {
+/// Everything one frame shows; also the payload of `--events` records.
+#[derive(Clone, Debug, serde::Serialize)]
+struct FrameState {
+  exercise: String,
+  set: u32,
+  sets: u32,
+  #[serde(rename = "rep")]
   current: u32,
+  #[serde(rename = "reps")]
   total: u32,
-  phase: &'a str,
+  phase: Phase,
   down_secs: f64,
   hold_secs: f64,
   up_secs: f64,
+  #[serde(rename = "remaining_secs", serialize_with = "events::secs")]
   remaining: Duration,
+  #[serde(rename = "rest_remaining_secs", serialize_with = "events::opt_secs")]
+  rest_remaining: Option<Duration>,
   paused: bool,
-  offset: usize,
-  pose_idx: usize,
-  max_drop_lines: usize,
+  /// 0 standing, 1 at the bottom of the squat.
+  depth: f64,
   stretch: f64,
+  /// Sideways shake of the figure, see `Tremor::sway`.
+  #[serde(skip)]
+  sway: f64,
+  /// 0–1 fill of the MOVE, HOLD, SET, TOTAL and REST bars.
+  #[serde(skip)]
+  move_progress: f64,
+  #[serde(skip)]
+  hold_progress: f64,
+  #[serde(skip)]
+  set_progress: f64,
+  #[serde(skip)]
+  total_progress: f64,
+  #[serde(skip)]
+  rest_progress: Option<f64>,
 }
 
 struct TerminalGuard;
 
+struct ScreenConsole {
+  renderer: Renderer,
+  theme: Theme,
+  /// `--big-clock`
+  big_clock: bool,
+  /// Dropped last, after the renderer's final cursor move.
+  _guard: TerminalGuard,
+}
+
+struct PlainConsole {
+  commands: mpsc::Receiver<InputAction>,
+  /// stdout, or stderr to leave stdout to `--events -`.
+  out: Box<dyn Write + Send>,
+  last_line: String,
+  /// Set, rep, phase and paused of the last frame printed.
+  last_frame: Option<(u32, u32, Phase, bool)>,
+}
+
 /* unsafe impl standard traits  **************************************************************************/
 
 /* impl standard traits  *********************************************************************************/
@@ -2382,6 +3472,289 @@ impl Drop for TerminalGuard {
 
 /* impl  *************************************************************************************************/
 
+impl Console {
+  /// `to_stderr` leaves stdout to `--events -` and implies plain mode.
+  fn new(args: &TerminalArgs, to_stderr: bool) -> Result<Self> {
+    if args.plain || to_stderr || !io::stdout().is_terminal() {
+      return Ok(Console::Plain(PlainConsole::new(to_stderr)));
+    }
+    let guard = TerminalGuard::new()?;
+    Ok(Console::Screen(ScreenConsole {
+      renderer: Renderer::new(),
+      theme: Theme::resolve(args.theme),
+      big_clock: args.big_clock,
+      _guard: guard,
+    }))
+  }
+
+  fn read_input(&mut self, timeout: Duration) -> Result<InputAction> {
+    match self {
+      Console::Screen(..) => read_input(timeout),
+      Console::Plain(plain) => Ok(plain.read_input(timeout)),
+    }
+  }
+
+  fn message(&mut self, message: &str, line2: &str) -> Result<()> {
+    match self {
+      Console::Screen(screen) => Ok(screen.renderer.draw(&message_lines(message, line2))?),
+      Console::Plain(plain) if line2.is_empty() => plain.line(message),
+      Console::Plain(plain) => plain.line(&format!("{} {}", message, line2)),
+    }
+  }
+
+  /// `caption` with `count` in big digits across the screen.
+  fn countdown(&mut self, caption: &str, count: u64) -> Result<()> {
+    match self {
+      Console::Screen(screen) => {
+        let lines = countdown_lines(caption, &count.to_string(), screen.theme.hold);
+        Ok(screen.renderer.draw(&lines)?)
+      }
+      Console::Plain(plain) => plain.line(&format!("{} {}", caption, count)),
+    }
+  }
+
+  fn frame(&mut self, state: &FrameState) -> Result<()> {
+    match self {
+      Console::Screen(screen) => {
+        let lines = frame_lines(state, &screen.theme, screen.big_clock);
+        Ok(screen.renderer.draw(&lines)?)
+      }
+      Console::Plain(plain) => plain.frame(state),
+    }
+  }
+}
+
+impl PlainConsole {
+  fn new(to_stderr: bool) -> Self {
+    let (sender, commands) = mpsc::channel();
+    // Blocks on stdin for the rest of the process; EOF (e.g. `< /dev/null` in CI) just
+    // means no commands.
+    thread::spawn(move || {
+      for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+          break;
+        };
+        match plain_command(&line) {
+          Some(action) => {
+            if sender.send(action).is_err() {
+              break;
+            }
+          }
+          None => tracing::warn!("unknown command {:?} (pause, resume, stop)", line.trim()),
+        }
+      }
+    });
+    let out: Box<dyn Write + Send> = if to_stderr {
+      Box::new(io::stderr())
+    } else {
+      Box::new(io::stdout())
+    };
+    Self::with_output(commands, out)
+  }
+
+  fn with_output(commands: mpsc::Receiver<InputAction>, out: Box<dyn Write + Send>) -> Self {
+    Self {
+      commands,
+      out,
+      last_line: String::new(),
+      last_frame: None,
+    }
+  }
+
+  fn read_input(&self, timeout: Duration) -> InputAction {
+    match self.commands.recv_timeout(timeout) {
+      Ok(action) => action,
+      Err(mpsc::RecvTimeoutError::Timeout) => InputAction::None,
+      Err(mpsc::RecvTimeoutError::Disconnected) => {
+        thread::sleep(timeout);
+        InputAction::None
+      }
+    }
+  }
+
+  /// Prints `line` unless it repeats the previous one.
+  fn line(&mut self, line: &str) -> Result<()> {
+    if self.last_line == line {
+      return Ok(());
+    }
+    self.last_line = line.to_string();
+    writeln!(self.out, "{}", line)?;
+    self.out.flush()?;
+    Ok(())
+  }
+
+  fn frame(&mut self, state: &FrameState) -> Result<()> {
+    let key = (state.set, state.current, state.phase, state.paused);
+    let Some(previous) = self.last_frame.replace(key) else {
+      self.line(&format!(
+        "{}  Set {}/{}",
+        state.exercise, state.set, state.sets
+      ))?;
+      return self.line(&plain_phase_line(state));
+    };
+    if previous == key {
+      return Ok(());
+    }
+    if state.paused {
+      return self.line("PAUSED");
+    }
+    if previous.3 && (previous.0, previous.1, previous.2) == (key.0, key.1, key.2) {
+      return self.line("RESUMED");
+    }
+    let new_set = previous.0 != state.set || previous.2 == Phase::Rest;
+    if new_set && state.rest_remaining.is_none() {
+      self.line(&format!(
+        "{}  Set {}/{}",
+        state.exercise, state.set, state.sets
+      ))?;
+    }
+    self.line(&plain_phase_line(state))
+  }
+}
+
+impl WebArgs {
+  fn workout(&self, title: &str, source: WorkoutSource) -> WebWorkout {
+    WebWorkout {
+      title: title.to_string(),
+      source,
+      swing_start: self.tremor.swing_start,
+      swing_stop: self.tremor.swing_stop,
+      freq: self.tremor.freq,
+      group: self.group,
+    }
+  }
+}
+
+impl TremorArgs {
+  fn tremor(&self) -> Tremor {
+    Tremor {
+      swing_start: self.swing_start,
+      swing_stop: self.swing_stop,
+      freq: self.freq,
+    }
+  }
+}
+
+impl TerminalArgs {
+  /// Hooks from config.toml first, then the `--hook` flags.
+  fn hooks(&self, config: &Config) -> Result<Vec<HookSpec>> {
+    let from_config = config
+      .hooks
+      .iter()
+      .map(|(event, command)| (event.as_str(), command.as_str()));
+    let from_flags = self
+      .hook
+      .chunks_exact(2)
+      .map(|pair| (pair[0].as_str(), pair[1].as_str()));
+    from_config
+      .chain(from_flags)
+      .map(|(event, command)| HookSpec::parse(event, command))
+      .collect()
+  }
+
+  /// Patterns from config.toml, overridden by the `--cue` flags.
+  fn cues(&self, config: &Config) -> Result<Cues> {
+    let from_config = config
+      .cues
+      .iter()
+      .map(|(event, pattern)| (event.as_str(), pattern.as_str()));
+    let from_flags = self
+      .cue
+      .iter()
+      .map(|cue| {
+        cue
+          .split_once('=')
+          .ok_or_else(|| eyre!("invalid --cue {:?}: expected EVENT=PATTERN", cue))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let overrides = from_config
+      .chain(from_flags)
+      .map(|(event, pattern)| cues::parse_override(event, pattern))
+      .collect::<Result<Vec<_>>>()?;
+    Cues::start(self.cues, overrides, &self.tone_command)
+  }
+}
+
+impl ServiceUnitArgs {
+  /// Checks the service command against the real CLI, so the unit can't hold flags this
+  /// binary doesn't accept.
+  fn unit(self) -> Result<ServiceUnit> {
+    let command = if self.command.is_empty() {
+      vec!["squat-web".to_string()]
+    } else {
+      self.command
+    };
+    let cli = Cli::try_parse_from(std::iter::once("trainer".to_string()).chain(command.clone()))
+      .map_err(|err| eyre!("invalid service command:\n{}", err.render()))?;
+    let addr = match cli.command {
+      Commands::SquatWeb(args) => args.web.addr,
+      Commands::Run(args) if args.web => args.web_args.addr,
+      _ => {
+        return Err(eyre!(
+          "the service command must be `squat-web` or `run --web`"
+        ));
+      }
+    };
+    if self.socket && addr.parse::<std::net::SocketAddr>().is_err() {
+      return Err(eyre!(
+        "--socket needs a numeric --addr such as 0.0.0.0:12002, got {:?}",
+        addr
+      ));
+    }
+    let bin = match self.bin {
+      Some(bin) => bin,
+      None => env::current_exe()?,
+    };
+    let bin = bin
+      .canonicalize()
+      .wrap_err_with(|| format!("trainer binary not found: {}", bin.display()))?;
+    let workdir = self
+      .workdir
+      .map(|dir| {
+        dir
+          .canonicalize()
+          .wrap_err_with(|| format!("workdir not found: {}", dir.display()))
+      })
+      .transpose()?;
+    let scope = self.target.scope;
+    let run_as = self.run_as.or_else(|| {
+      (scope == ServiceScope::System)
+        .then(|| env::var("SUDO_USER").or_else(|_| env::var("USER")).ok())
+        .flatten()
+    });
+    Ok(ServiceUnit {
+      name: self.target.name,
+      scope,
+      bin,
+      args: command,
+      run_as,
+      run_group: self.run_group,
+      workdir,
+      hardening: !self.no_hardening,
+      socket: self.socket.then_some(addr),
+    })
+  }
+}
+
+impl FilterArgs {
+  fn to_filter(&self) -> SessionFilter {
+    SessionFilter {
+      since: self.since,
+      until: self.until,
+      exercise: self.exercise.clone(),
+      completed_only: self.completed,
+    }
+  }
+
+  fn load(&self) -> Result<Vec<SessionRecord>> {
+    let filter = self.to_filter();
+    let mut records = history::load()?;
+    records.retain(|record| filter.matches(record));
+    records.sort_by_key(|record| record.started_at);
+    Ok(records)
+  }
+}
+
 /* fn  ***************************************************************************************************/
 
 fn init_tracing() -> Result<()> {
@@ -2395,7 +3768,11 @@ fn init_tracing() -> Result<()> {
 
   tracing_subscriber::registry()
     .with(env_filter)
-    .with(tracing_subscriber::fmt::layer())
+    .with(tracing_subscriber::fmt::layer().with_writer(|| {
+      // Log lines land in the middle of the screen; have the next frame redraw it all.
+      render::foreign_output();
+      io::stderr()
+    }))
     .with(ErrorLayer::default())
     .try_init()?;
 
@@ -2410,31 +3787,84 @@ fn format_mmss_millis(duration: Duration) -> String {
   format!("{:02}:{:02}.{:03}", minutes, seconds, millis)
 }
 
+/// `MM:SS`, rounded up like a timer: it shows 00:01 until the time is up.
+fn format_clock(duration: Duration) -> String {
+  let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+  format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
 fn terminal_rows() -> usize {
   terminal::size()
     .map(|(_, rows)| rows as usize)
     .unwrap_or(DEFAULT_ROWS)
 }
 
-fn squat_web_html(
-  duration: u64,
-  count: u32,
-  sets: u32,
-  interval: u64,
-  swing_start: f64,
-  swing_stop: f64,
-  freq: f64,
-) -> String {
-  SQUAT_WEB_HTML
-    .replace("__DURATION__", &duration.to_string())
-    .replace("__COUNT__", &count.to_string())
-    .replace("__SETS__", &sets.to_string())
-    .replace("__INTERVAL__", &interval.to_string())
-    .replace("__VERSION__", APP_VERSION)
-    .replace("__SWING_START__", &format!("{:.3}", swing_start))
-    .replace("__SWING_STOP__", &format!("{:.3}", swing_stop))
-    .replace("__FREQ__", &format!("{:.3}", freq))
-    .replace("__HOLD__", &format!("{:.1}", HOLD_SECS))
+fn terminal_cols() -> usize {
+  terminal::size()
+    .map(|(cols, _)| cols as usize)
+    .unwrap_or(DEFAULT_COLS)
+}
+
+fn squat_web_html(timeline: &WorkoutTimeline, workout: &WebWorkout) -> String {
+  let first = timeline.sets().first();
+  fill_placeholders(
+    SQUAT_WEB_HTML,
+    &[
+      (
+        "__EXERCISE__",
+        &html_escape(first.map_or("", |set| set.exercise.as_str())),
+      ),
+      ("__COUNT__", &first.map_or(0, |set| set.reps).to_string()),
+      ("__SETS__", &timeline.sets().len().to_string()),
+      ("__VERSION__", APP_VERSION),
+      // `<` only shows up inside JSON strings, where `\u003c` means the same and can't
+      // close the script element.
+      (
+        "__CONFIG__",
+        &workout.to_json(timeline).replace('<', "\\u003c"),
+      ),
+    ],
+  )
+}
+
+/// Replaces `__NAME__` placeholders in a single pass, so a value that happens to contain
+/// one (a plan named `__CONFIG__`) is left as it is.
+fn fill_placeholders(template: &str, values: &[(&str, &str)]) -> String {
+  let mut out = String::with_capacity(template.len());
+  let mut rest = template;
+  while let Some(start) = rest.find("__") {
+    match values
+      .iter()
+      .find(|(key, _)| rest[start..].starts_with(key))
+    {
+      Some((key, value)) => {
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[start + key.len()..];
+      }
+      None => {
+        out.push_str(&rest[..start + 1]);
+        rest = &rest[start + 1..];
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+fn html_escape(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for ch in text.chars() {
+    match ch {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      _ => out.push(ch),
+    }
+  }
+  out
 }
 
 fn read_input(timeout: Duration) -> Result<InputAction> {
@@ -2453,82 +3883,183 @@ fn read_input(timeout: Duration) -> Result<InputAction> {
   }
 }
 
-fn build_figure_lines(offset: usize, pose_idx: usize, max_drop_lines: usize) -> Vec<String> {
-  let mut lines = Vec::new();
-  let clamped_offset = offset.min(max_drop_lines);
-  lines.extend(std::iter::repeat(String::new()).take(clamped_offset));
-  let pose = &POSES[pose_idx.min(POSE_COUNT - 1)];
-  lines.extend(pose.iter().map(|line| (*line).to_string()));
-
-  let total_body = max_drop_lines + POSE_LINES;
-  let current_body = clamped_offset + POSE_LINES;
-  if total_body > current_body {
-    lines.extend(std::iter::repeat(String::new()).take(total_body - current_body));
+fn frame_lines(state: &FrameState, theme: &Theme, big_clock: bool) -> Vec<Line> {
+  let status = match state.rest_remaining {
+    _ if state.paused => Line::default().push("PAUSED", theme.hold),
+    Some(rest) => Line::default().push(format!("REST {}", format_mmss_millis(rest)), theme.rest),
+    None => Line::from("RUNNING"),
+  };
+  let bar_width = terminal_cols()
+    .saturating_sub(BAR_LABEL_COLS + BAR_VALUE_COLS)
+    .max(BAR_MIN_COLS);
+  let move_style = match state.phase {
+    Phase::Up => theme.up,
+    _ => theme.down,
+  };
+  let mut lines = vec![
+    Line::from(format!(
+      "{}  Set: {}/{}  Rep: {}/{}",
+      state.exercise, state.set, state.sets, state.current, state.total
+    )),
+    Line::default()
+      .push("Phase: ", Style::default())
+      .push(state.phase.label(), theme.phase(state.phase))
+      .push(
+        format!(
+          "  Tempo: down {:.1}s / hold {:.1}s / up {:.1}s",
+          state.down_secs, state.hold_secs, state.up_secs
+        ),
+        Style::default(),
+      ),
+    Line::from(format!("伸長(100=伸,0=縮): {:.1}", state.stretch)),
+    Line::from(format!(
+      "Time left: {}",
+      format_mmss_millis(state.remaining)
+    )),
+    Line::default()
+      .push("Status: ", Style::default())
+      .push_line(status),
+    progress_bar("MOVE", state.move_progress, bar_width, move_style, theme),
+    progress_bar("HOLD", state.hold_progress, bar_width, theme.hold, theme),
+    progress_bar("SET", state.set_progress, bar_width, theme.progress, theme),
+    progress_bar(
+      "TOTAL",
+      state.total_progress,
+      bar_width,
+      theme.progress,
+      theme,
+    ),
+    state.rest_progress.map_or_else(Line::default, |rest| {
+      progress_bar("REST", rest, bar_width, theme.rest, theme)
+    }),
+    Line::default().push(
+      "Controls: SPACE=Pause/Resume  ESC=Quit  Ctrl+C=Quit",
+      theme.muted,
+    ),
+    Line::default(),
+  ];
+  let (cols, rows) = (
+    terminal_cols(),
+    terminal_rows().saturating_sub(HEADER_LINES),
+  );
+  if let Some(rest) = state.rest_remaining {
+    // The rest timer takes the figure's place.
+    let clock = digits::render(&format_clock(rest), cols, rows).unwrap_or_default();
+    lines.extend(centered(&clock, cols, rows, theme.rest));
+    return lines;
   }
+  // With --big-clock the figure leaves at least half the width to the clock.
+  let max_figure_cols = if big_clock { cols / 2 } else { cols };
+  let figure = figure::draw(max_figure_cols, rows, state.depth, state.sway);
+  let figure_cols = figure.first().map_or(0, |line| line.chars().count()) + BIG_CLOCK_GAP;
+  let clock = big_clock
+    .then(|| {
+      digits::render(
+        &format_clock(state.remaining),
+        cols.saturating_sub(figure_cols),
+        rows,
+      )
+    })
+    .flatten()
+    .unwrap_or_default();
+  let clock_top = figure.len().saturating_sub(clock.len()) / 2;
+  lines.extend(figure.into_iter().enumerate().map(|(row, figure_line)| {
+    match row.checked_sub(clock_top).and_then(|idx| clock.get(idx)) {
+      Some(clock_line) => Line::from(format!("{:<width$}", figure_line, width = figure_cols))
+        .push(clock_line.clone(), Style::default()),
+      None => Line::from(figure_line),
+    }
+  }));
+  lines
+}
 
-  lines.push(FLOOR.to_string());
+/// `caption` over `text` in big digits, centered on the screen.
+fn countdown_lines(caption: &str, text: &str, style: Style) -> Vec<Line> {
+  let (cols, rows) = (terminal_cols(), terminal_rows());
+  // The caption and a blank row go above the digits.
+  let Some(big) = digits::render(text, cols, rows.saturating_sub(2)) else {
+    return message_lines(caption, text);
+  };
+  let top = rows.saturating_sub(big.len() + 2) / 2;
+  let caption_pad = " ".repeat(cols.saturating_sub(caption.chars().count()) / 2);
+  let mut lines = vec![Line::default(); top];
+  lines.push(Line::from(format!("{}{}", caption_pad, caption)));
+  lines.push(Line::default());
+  lines.extend(centered(&big, cols, big.len(), style));
   lines
 }
 
-fn draw_frame(stdout: &mut io::Stdout, state: &FrameState) -> Result<()> {
-  execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
-
-  let status = if state.paused { "PAUSED" } else { "RUNNING" };
-  let mut output = String::new();
-  output.push_str(&format!(
-    "Slow Squat  Rep: {}/{}\r\n",
-    state.current, state.total
-  ));
-  output.push_str(&format!(
-    "Phase: {}  Tempo: down {:.1}s / hold {:.1}s / up {:.1}s\r\n",
-    state.phase, state.down_secs, state.hold_secs, state.up_secs
-  ));
-  output.push_str(&format!("伸長(100=伸,0=縮): {:.1}\r\n", state.stretch));
-  output.push_str(&format!(
-    "Time left: {}\r\n",
-    format_mmss_millis(state.remaining)
-  ));
-  output.push_str(&format!("Status: {}\r\n", status));
-  output.push_str("Controls: SPACE=Pause/Resume  ESC=Quit  Ctrl+C=Quit\r\n\r\n");
-
-  let figure_lines = build_figure_lines(state.offset, state.pose_idx, state.max_drop_lines);
-  for (idx, line) in figure_lines.iter().enumerate() {
-    output.push_str(line);
-    if idx + 1 < figure_lines.len() {
-      output.push_str("\r\n");
-    }
-  }
+/// Puts `block` in the middle of a `cols`×`rows` area.
+fn centered(block: &[String], cols: usize, rows: usize, style: Style) -> Vec<Line> {
+  let top = rows.saturating_sub(block.len()) / 2;
+  let pad = " ".repeat(cols.saturating_sub(digits::width(block)) / 2);
+  let mut lines = vec![Line::default(); top];
+  lines.extend(block.iter().map(|line| {
+    Line::default()
+      .push(pad.clone(), Style::default())
+      .push(line.clone(), style)
+  }));
+  lines
+}
 
-  write!(stdout, "{}", output)?;
-  stdout.flush()?;
-  Ok(())
+/// `TOTAL ██████▍░░░░░░░  42.5%`, filled in eighths of a cell.
+fn progress_bar(label: &str, fraction: f64, width: usize, fill: Style, theme: &Theme) -> Line {
+  const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+  let fraction = fraction.clamp(0.0, 1.0);
+  let eighths = (fraction * (width * 8) as f64).round() as usize;
+  let (full, part) = (eighths / 8, eighths % 8);
+  let mut filled = "█".repeat(full);
+  let mut empty = width - full;
+  if part > 0 {
+    filled.push(PARTIAL[part]);
+    empty -= 1;
+  }
+  Line::default()
+    .push(
+      format!("{:<width$}", label, width = BAR_LABEL_COLS),
+      theme.muted,
+    )
+    .push(filled, fill)
+    .push("░".repeat(empty), theme.track)
+    .push(format!(" {:>5.1}%", fraction * 100.0), Style::default())
 }
 
-fn draw_message(stdout: &mut io::Stdout, message: &str, line2: &str) -> Result<()> {
-  execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
-  write!(stdout, "{}\r\n", message)?;
+fn message_lines(message: &str, line2: &str) -> Vec<Line> {
+  let mut lines = vec![Line::from(message)];
   if !line2.is_empty() {
-    write!(stdout, "{}\r\n", line2)?;
+    lines.push(Line::from(line2));
   }
-  stdout.flush()?;
-  Ok(())
+  lines
 }
 
-fn run_countdown(stdout: &mut io::Stdout, seconds: u64, exit_flag: &AtomicBool) -> Result<bool> {
+fn run_countdown(
+  console: &mut Console,
+  events: &mut EventLog,
+  cues: &Cues,
+  seconds: u64,
+  shutdown: &Shutdown,
+  first: &TimelineState,
+) -> Result<bool> {
   if seconds == 0 {
     return Ok(true);
   }
 
   for remaining in (1..=seconds).rev() {
-    draw_message(stdout, "Starting in...", &format!("{}", remaining))?;
+    events.emit(
+      WorkoutEvent::Countdown,
+      &serde_json::json!({ "count": remaining, "set": first.set, "sets": first.sets }),
+    );
+    cues.play(Cue::Countdown);
+    unless_shutting_down(shutdown, console.countdown("Starting in...", remaining))?;
     let started = Instant::now();
     while started.elapsed() < Duration::from_secs(1) {
-      if exit_flag.load(Ordering::SeqCst) {
+      if shutdown.requested().is_some() {
         return Ok(false);
       }
-      match read_input(Duration::from_millis(50))? {
-        InputAction::Exit => return Ok(false),
-        _ => {}
+      if let InputAction::Exit =
+        unless_shutting_down(shutdown, console.read_input(Duration::from_millis(50)))?
+      {
+        return Ok(false);
       }
     }
   }
@@ -2536,53 +4067,284 @@ fn run_countdown(stdout: &mut io::Stdout, seconds: u64, exit_flag: &AtomicBool)
   Ok(true)
 }
 
-fn run_squat(args: SquatArgs) -> Result<()> {
-  let exit_flag = Arc::new(AtomicBool::new(false));
-  let exit_flag_clone = exit_flag.clone();
-  ctrlc::set_handler(move || {
-    exit_flag_clone.store(true, Ordering::SeqCst);
-  })?;
-
-  let _terminal_guard = TerminalGuard::new()?;
-  let mut stdout = io::stdout();
-
-  if !run_countdown(&mut stdout, args.countdown, &exit_flag)? {
-    draw_message(&mut stdout, "Stopped.", &format!("Reps: 0/{}", args.count))?;
-    return Ok(());
+fn frame_state(state: &TimelineState, paused: bool, sway: f64) -> FrameState {
+  let clamped = state.depth.clamp(0.0, 1.0);
+  let stretch = (1.0 - clamped) * 100.0;
+  // Like the web page: MOVE fills during DOWN and UP and stays full through HOLD.
+  let (move_progress, hold_progress) = match state.phase {
+    Phase::Down => (state.phase_progress, 0.0),
+    Phase::Hold => (1.0, state.phase_progress),
+    Phase::Up => (state.phase_progress, 1.0),
+    Phase::Rest => (0.0, 0.0),
+  };
+
+  FrameState {
+    exercise: state.exercise.clone(),
+    set: state.set,
+    sets: state.sets,
+    current: state.rep,
+    total: state.reps,
+    phase: state.phase,
+    down_secs: state.tempo.down,
+    hold_secs: state.tempo.hold,
+    up_secs: state.tempo.up,
+    remaining: state.remaining,
+    rest_remaining: (state.phase == Phase::Rest).then_some(state.rest_remaining),
+    paused,
+    depth: clamped,
+    stretch,
+    sway,
+    move_progress,
+    hold_progress,
+    set_progress: state.set_progress,
+    total_progress: state.progress,
+    rest_progress: (state.phase == Phase::Rest).then_some(state.phase_progress),
+  }
+}
+
+/// Events between two consecutive frames; `set_complete` carries the frame of the set
+/// that just ended. A new rep emits `rep_start` and then `phase` (DOWN) for the same
+/// frame on purpose: `phase` is the one event for every phase change, so `on_phase=DOWN`
+/// hooks and `select(.event == "phase")` readers see the first DOWN of each rep too.
+fn frame_events<'a>(
+  previous: Option<&'a FrameState>,
+  state: &'a FrameState,
+) -> Vec<(WorkoutEvent, &'a FrameState)> {
+  let resting = state.rest_remaining.is_some();
+  let Some(previous) = previous else {
+    return if resting {
+      vec![(WorkoutEvent::Phase, state)]
+    } else {
+      vec![
+        (WorkoutEvent::RepStart, state),
+        (WorkoutEvent::Phase, state),
+      ]
+    };
+  };
+  let mut events = Vec::new();
+  let was_resting = previous.rest_remaining.is_some();
+  if !was_resting && (resting || previous.set != state.set) {
+    events.push((WorkoutEvent::SetComplete, previous));
+  }
+  if !resting && (was_resting || previous.set != state.set || previous.current != state.current) {
+    events.push((WorkoutEvent::RepStart, state));
+  }
+  if previous.phase != state.phase || !events.is_empty() {
+    events.push((WorkoutEvent::Phase, state));
+  }
+  // After the transitions, which can land in the same frame as a key press.
+  if previous.paused != state.paused {
+    let event = if state.paused {
+      WorkoutEvent::Pause
+    } else {
+      WorkoutEvent::Resume
+    };
+    events.push((event, state));
+  }
+  events
+}
+
+/// The cue for a frame's events: a finished set wins over the REST or DOWN that follows it.
+fn frame_cue(events: &[(WorkoutEvent, &FrameState)]) -> Option<Cue> {
+  if events
+    .iter()
+    .any(|(event, _)| *event == WorkoutEvent::SetComplete)
+  {
+    return Some(Cue::SetComplete);
+  }
+  events
+    .iter()
+    .find(|(event, _)| *event == WorkoutEvent::Phase)
+    .map(|(_, frame)| match frame.phase {
+      Phase::Down => Cue::Down,
+      Phase::Hold => Cue::Hold,
+      Phase::Up => Cue::Up,
+      Phase::Rest => Cue::Rest,
+    })
+}
+
+fn plain_command(line: &str) -> Option<InputAction> {
+  match line.trim().to_ascii_lowercase().as_str() {
+    "" | "p" | "space" => Some(InputAction::TogglePause),
+    "pause" => Some(InputAction::Pause),
+    "r" | "resume" => Some(InputAction::Resume),
+    "q" | "quit" | "stop" | "exit" => Some(InputAction::Exit),
+    _ => None,
+  }
+}
+
+/// `Rep 3/20 DOWN 4.0s`, or `REST 60s` between sets.
+fn plain_phase_line(state: &FrameState) -> String {
+  if let Some(rest) = state.rest_remaining {
+    return format!(
+      "REST {}s",
+      rest.as_secs() + u64::from(rest.subsec_nanos() > 0)
+    );
   }
+  let secs = match state.phase {
+    Phase::Down => state.down_secs,
+    Phase::Hold => state.hold_secs,
+    _ => state.up_secs,
+  };
+  format!(
+    "Rep {}/{} {} {:.1}s",
+    state.current, state.total, state.phase, secs
+  )
+}
 
-  let total_duration = Duration::from_secs(args.duration);
-  let rep_duration = total_duration.as_secs_f64() / args.count as f64;
-  if rep_duration <= HOLD_SECS {
-    return Err(color_eyre::eyre::eyre!(
-      "duration/count must be greater than {:.1}s to allow a {:.1}s hold",
-      HOLD_SECS,
-      HOLD_SECS
-    ));
+/// After a shutdown signal the terminal may already be gone (SIGHUP); its I/O errors must
+/// not keep the session from being recorded.
+fn unless_shutting_down<T: Default>(shutdown: &Shutdown, result: Result<T>) -> Result<T> {
+  match result {
+    Err(err) if shutdown.requested().is_some() => {
+      tracing::debug!("ignoring terminal error during shutdown: {}", err);
+      Ok(T::default())
+    }
+    result => result,
   }
-  let down_duration = (rep_duration - HOLD_SECS) / 2.0;
-  let up_duration = down_duration;
+}
+
+fn run_squat(args: SquatArgs) -> Result<()> {
+  if args.resume {
+    let Some(checkpoint) = checkpoint::load()? else {
+      return Err(eyre!("no unfinished workout to resume"));
+    };
+    let timeline = checkpoint.timeline();
+    if checkpoint.completed_reps >= timeline.total_reps() {
+      checkpoint::clear()?;
+      return Err(eyre!("the saved workout was already finished"));
+    }
+    tracing::info!(
+      "resuming {:?} from set {}/{}, {}/{} reps done",
+      checkpoint.exercise,
+      checkpoint.set,
+      timeline.sets().len(),
+      checkpoint.completed_reps,
+      timeline.total_reps()
+    );
+    let title = checkpoint.exercise.clone();
+    return play_terminal(
+      &timeline,
+      &args.terminal,
+      args.tremor.tremor(),
+      &title,
+      Some(checkpoint),
+    );
+  }
+  let timeline = UniformWorkout {
+    duration: args.duration,
+    count: args.count,
+    sets: args.sets,
+    interval: args.interval,
+    tempo: args.tempo,
+  }
+  .timeline()?;
+  play_terminal(
+    &timeline,
+    &args.terminal,
+    args.tremor.tremor(),
+    DEFAULT_EXERCISE,
+    None,
+  )
+}
+
+/// Plays `timeline`, checkpointing the position every second; `resume` starts at the rep
+/// after the checkpoint's last completed one.
+fn play_terminal(
+  timeline: &WorkoutTimeline,
+  args: &TerminalArgs,
+  tremor: Tremor,
+  title: &str,
+  resume: Option<Checkpoint>,
+) -> Result<()> {
+  tremor.validate()?;
+  let total_reps = timeline.total_reps();
+  let started_at = Local::now().fixed_offset();
+  let resume_at = resume.as_ref().map_or(Duration::ZERO, |checkpoint| {
+    timeline.rep_start(checkpoint.completed_reps)
+  });
+  // History only gets what this run did; the earlier run was recorded when it stopped.
+  let before = timeline.at(resume_at);
+  let config = SessionConfig::from_timeline(timeline);
+  let checkpoint_at = |state: &TimelineState, elapsed: Duration, pauses: u32, paused: Duration| {
+    let (first_started, earlier_pauses, earlier_paused) =
+      resume.as_ref().map_or((started_at, 0, 0.0), |checkpoint| {
+        (
+          checkpoint.started_at,
+          checkpoint.pauses,
+          checkpoint.paused_secs,
+        )
+      });
+    Checkpoint {
+      started_at: first_started,
+      saved_at: Local::now().fixed_offset(),
+      exercise: title.to_string(),
+      elapsed_secs: elapsed.as_secs_f64(),
+      set: state.set,
+      rep: state.rep,
+      completed_reps: state.total_completed_reps,
+      pauses: earlier_pauses + pauses,
+      paused_secs: earlier_paused + paused.as_secs_f64(),
+      config: config.clone(),
+    }
+  };
+  let mut last_checkpoint: Option<Instant> = None;
+
+  let shutdown = Shutdown::install()?;
+
+  let countdown = args.countdown;
+  let events_on_stdout = args.events.as_deref() == Some("-");
+  let user_config = Config::load()?;
+  let mut events = EventLog::open(
+    args.events.as_deref(),
+    Hooks::start(args.hooks(&user_config)?),
+  )?;
+  let cues = args.cues(&user_config)?;
+  let mut console = Console::new(args, events_on_stdout)?;
+
+  let mut aborted = !run_countdown(
+    &mut console,
+    &mut events,
+    &cues,
+    countdown,
+    &shutdown,
+    &before,
+  )?;
 
   let mut paused = false;
   let mut paused_at: Option<Instant> = None;
   let mut paused_total = Duration::ZERO;
+  let mut pauses = 0;
   let start = Instant::now();
 
-  let mut aborted = false;
-  let mut completed_reps = 0;
+  let mut progress = before.clone();
+  let mut position = resume_at;
+  let mut last_frame: Option<FrameState> = None;
+  let mut last_tick: Option<u64> = None;
+  let mut last_beat: Option<u64> = None;
 
   loop {
-    if exit_flag.load(Ordering::SeqCst) {
+    if aborted || shutdown.requested().is_some() {
       aborted = true;
       break;
     }
 
-    match read_input(Duration::from_millis(TICK_MS))? {
+    let input = unless_shutting_down(
+      &shutdown,
+      console.read_input(Duration::from_millis(TICK_MS)),
+    )?;
+    let toggle = match input {
+      InputAction::TogglePause => true,
+      InputAction::Pause => !paused,
+      InputAction::Resume => paused,
+      _ => false,
+    };
+    match input {
       InputAction::Exit => {
         aborted = true;
         break;
       }
-      InputAction::TogglePause => {
+      _ if toggle => {
         if paused {
           if let Some(paused_start) = paused_at.take() {
             paused_total =
@@ -2592,9 +4354,10 @@ fn run_squat(args: SquatArgs) -> Result<()> {
         } else {
           paused = true;
           paused_at = Some(Instant::now());
+          pauses += 1;
         }
       }
-      InputAction::None => {}
+      _ => {}
     }
 
     let now = Instant::now();
@@ -2603,126 +4366,388 @@ fn run_squat(args: SquatArgs) -> Result<()> {
     } else {
       now
     };
-    let elapsed = effective_now
-      .saturating_duration_since(start)
-      .saturating_sub(paused_total);
-
-    if elapsed >= total_duration {
+    let elapsed = resume_at
+      + effective_now
+        .saturating_duration_since(start)
+        .saturating_sub(paused_total);
+
+    let timeline_state = timeline.at(elapsed);
+    progress = timeline_state.clone();
+    position = elapsed;
+    if timeline_state.done {
       break;
     }
+    if last_checkpoint.is_none_or(|saved| saved.elapsed() >= CHECKPOINT_INTERVAL) {
+      let saved = checkpoint_at(&timeline_state, elapsed, pauses, paused_total);
+      if let Err(err) = checkpoint::save(&saved) {
+        tracing::warn!("failed to save checkpoint: {:#}", err);
+      }
+      last_checkpoint = Some(Instant::now());
+    }
 
-    let elapsed_secs = elapsed.as_secs_f64();
-    let rep_index = (elapsed_secs / rep_duration).floor() as u32;
-    let completed = rep_index.min(args.count);
-    completed_reps = completed;
-    let within_rep = elapsed_secs - (rep_index as f64 * rep_duration);
-
-    let (phase, progress) = if within_rep < down_duration {
-      ("DOWN", within_rep / down_duration)
-    } else if within_rep < down_duration + HOLD_SECS {
-      ("HOLD", 1.0)
-    } else {
-      (
-        "UP",
-        1.0 - (within_rep - down_duration - HOLD_SECS) / up_duration,
-      )
-    };
-
-    let clamped = progress.clamp(0.0, 1.0);
-    let max_drop_lines = terminal_rows().saturating_sub(HEADER_LINES + POSE_LINES + FLOOR_LINES);
-    let offset = (clamped * max_drop_lines as f64)
-      .round()
-      .min(max_drop_lines as f64) as usize;
-    let pose_idx = (clamped * (POSE_COUNT.saturating_sub(1)) as f64).round() as usize;
-    let stretch = (1.0 - clamped) * 100.0;
-
-    let remaining = total_duration.saturating_sub(elapsed);
-    let current_rep = (completed.saturating_add(1)).min(args.count);
-    let state = FrameState {
-      current: current_rep,
-      total: args.count,
-      phase,
-      down_secs: down_duration,
-      hold_secs: HOLD_SECS,
-      up_secs: up_duration,
-      remaining,
-      paused,
-      offset,
-      pose_idx,
-      max_drop_lines,
-      stretch,
+    // The shake dies down over a rest, as on the web page.
+    let calm = match timeline_state.phase {
+      Phase::Rest => 1.0 - timeline_state.phase_progress,
+      _ => 1.0,
     };
+    let sway = tremor.sway(elapsed, timeline_state.progress, calm);
+    let state = frame_state(&timeline_state, paused, sway);
+    let changes = frame_events(last_frame.as_ref(), &state);
+    let cue = frame_cue(&changes);
+    for (event, frame) in changes {
+      events.emit(event, frame);
+    }
+    // The metronome counts seconds of DOWN/HOLD/UP and gives way to phase cues.
+    let beat = (!paused && state.rest_remaining.is_none())
+      .then_some(timeline_state.active_elapsed.as_secs());
+    match cue {
+      Some(cue) => cues.play(cue),
+      None if beat.is_some() && last_beat.is_some() && beat != last_beat => cues.play(Cue::Tick),
+      None => {}
+    }
+    if beat.is_some() {
+      last_beat = beat;
+    }
+    let rest_remaining = state.rest_remaining;
+    last_frame = Some(state);
+    if let Some(rest) = rest_remaining
+      && !paused
+      && countdown > 0
+      && rest <= Duration::from_secs(countdown)
+    {
+      let seconds = (rest.as_secs() + u64::from(rest.subsec_nanos() > 0)).max(1);
+      if last_tick != Some(seconds) {
+        last_tick = Some(seconds);
+        events.emit(
+          WorkoutEvent::Countdown,
+          &serde_json::json!({ "count": seconds, "set": timeline_state.set, "sets": timeline_state.sets }),
+        );
+        cues.play(Cue::Countdown);
+      }
+      unless_shutting_down(
+        &shutdown,
+        console.countdown(
+          &format!("Set {}/{} in...", timeline_state.set, timeline_state.sets),
+          seconds,
+        ),
+      )?;
+      continue;
+    }
 
-    draw_frame(&mut stdout, &state)?;
+    if let Some(state) = &last_frame {
+      unless_shutting_down(&shutdown, console.frame(state))?;
+    }
   }
 
-  if aborted {
-    draw_message(
-      &mut stdout,
-      "Stopped.",
-      &format!("Reps: {}/{}", completed_reps, args.count),
-    )?;
+  // SIGTERM and SIGHUP end the session from outside; only Ctrl+C / ESC mean the user quit.
+  let status = match shutdown.requested() {
+    _ if !aborted => SessionStatus::Completed,
+    Some(ShutdownSignal::Terminate | ShutdownSignal::Hangup) => SessionStatus::Interrupted,
+    _ => SessionStatus::Aborted,
+  };
+  let message = match status {
+    SessionStatus::Completed => "Complete!",
+    SessionStatus::Aborted => "Stopped.",
+    SessionStatus::Interrupted => "Interrupted.",
+  };
+  let reps_done = if status == SessionStatus::Completed {
+    total_reps
   } else {
-    draw_message(
-      &mut stdout,
-      "Complete!",
-      &format!("Reps: {}/{}", args.count, args.count),
-    )?;
+    progress.total_completed_reps
+  };
+  let final_frame = frame_state(&progress, paused, 0.0);
+  if status == SessionStatus::Completed {
+    if let Some(last) = last_frame
+      .as_ref()
+      .filter(|last| last.rest_remaining.is_none())
+    {
+      events.emit(WorkoutEvent::SetComplete, last);
+    }
+    events.emit(WorkoutEvent::WorkoutComplete, &final_frame);
+    cues.play(Cue::Complete);
+  } else {
+    let frame = last_frame.as_ref().unwrap_or(&final_frame);
+    let mut fields = serde_json::to_value(frame)?;
+    fields["status"] = serde_json::json!(status.label());
+    events.emit(WorkoutEvent::Abort, &fields);
+  }
+  unless_shutting_down(
+    &shutdown,
+    console.message(message, &format!("Reps: {}/{}", reps_done, total_reps)),
+  )?;
+  drop(console);
+  if let Some(signal) = shutdown.requested() {
+    tracing::info!(
+      "received {}, session recorded as {}",
+      signal.name(),
+      status.label()
+    );
+  }
+
+  if let Some(paused_start) = paused_at {
+    paused_total = paused_total.saturating_add(paused_start.elapsed());
+  }
+  if status == SessionStatus::Completed {
+    if let Err(err) = checkpoint::clear() {
+      tracing::warn!("failed to remove checkpoint: {:#}", err);
+    }
+  } else if last_checkpoint.is_some() {
+    let saved = checkpoint_at(&progress, position, pauses, paused_total);
+    match checkpoint::save(&saved) {
+      Ok(()) => tracing::info!(
+        "progress saved at {}/{} reps; continue with `trainer squat --resume`",
+        saved.completed_reps,
+        total_reps
+      ),
+      Err(err) => tracing::warn!("failed to save checkpoint: {:#}", err),
+    }
   }
+  let run = RunProgress::between(timeline, &before, &progress);
+  history::record(&SessionRecord {
+    started_at,
+    ended_at: Local::now().fixed_offset(),
+    source: SessionSource::Terminal,
+    exercise: title.to_string(),
+    status,
+    reps_done: run.reps_done,
+    reps_total: run.reps_total,
+    sets_done: run.sets_done,
+    sets_total: run.sets_total,
+    pauses,
+    paused_secs: paused_total.as_secs_f64(),
+    active_secs: run.active_secs,
+    config,
+  });
 
   Ok(())
 }
 
 fn run_squat_web(args: SquatWebArgs) -> Result<()> {
-  let rep_duration = args.duration as f64 / args.count as f64;
-  if rep_duration <= HOLD_SECS {
-    return Err(color_eyre::eyre::eyre!(
-      "duration/count must be greater than {:.1}s to allow a {:.1}s hold",
-      HOLD_SECS,
-      HOLD_SECS
-    ));
-  }
-  if args.swing_start.is_sign_negative()
-    || args.swing_stop.is_sign_negative()
-    || args.freq.is_sign_negative()
-  {
-    return Err(color_eyre::eyre::eyre!(
-      "swing-start, swing-stop, and freq must be >= 0"
-    ));
-  }
-  let exit_flag = Arc::new(AtomicBool::new(false));
-  let exit_flag_clone = exit_flag.clone();
-  ctrlc::set_handler(move || {
-    exit_flag_clone.store(true, Ordering::SeqCst);
-  })?;
-
-  let server = Server::http(&args.addr).map_err(|err| color_eyre::eyre::eyre!(err))?;
-  let html = squat_web_html(
-    args.duration,
-    args.count,
-    args.sets,
-    args.interval,
-    args.swing_start,
-    args.swing_stop,
-    args.freq,
+  let workout = args.web.workout(
+    DEFAULT_EXERCISE,
+    WorkoutSource::Uniform(UniformWorkout {
+      duration: args.duration,
+      count: args.count,
+      sets: args.sets,
+      interval: args.interval,
+      tempo: args.tempo,
+    }),
   );
-  let content_type = Header::from_bytes("Content-Type", "text/html; charset=utf-8")
-    .map_err(|_| color_eyre::eyre::eyre!("invalid content-type header"))?;
+  serve_web(&workout, &args.web.addr)
+}
 
-  while !exit_flag.load(Ordering::SeqCst) {
-    match server.recv_timeout(Duration::from_millis(200)) {
-      Ok(Some(request)) => {
-        let response = Response::from_string(html.clone()).with_header(content_type.clone());
-        let _ = request.respond(response);
-      }
-      Ok(None) => {}
-      Err(err) => return Err(err.into()),
+fn serve_web(workout: &WebWorkout, addr: &str) -> Result<()> {
+  workout.validate()?;
+  let listener = systemd::listen_socket()?;
+  let shutdown = Shutdown::install()?;
+
+  let server = match listener {
+    Some(listener) => {
+      tracing::info!("using the socket from systemd; --addr {} is ignored", addr);
+      Server::from_listener(listener, None)
     }
+    None => Server::http(addr),
   }
+  .map_err(|err| eyre!(err))?;
+  let listening = server.server_addr().to_string();
+  let mut notifier = Notifier::from_env();
+  let sessions = Sessions::new(workout.group);
+  // `session` names the client's session and isn't part of the workout.
+  let client = |request: &Request| {
+    web::query_params(request)
+      .into_iter()
+      .rfind(|(key, _)| key == SESSION_PARAM)
+      .map(|(_, id)| id)
+  };
+  let requested = |request: &Request| -> Result<(WebWorkout, WorkoutTimeline)> {
+    let mut params = web::query_params(request);
+    params.retain(|(key, _)| key != SESSION_PARAM);
+    let workout = workout.with_query(&params)?;
+    let timeline = workout.timeline()?;
+    Ok((workout, timeline))
+  };
+  let router = Router::new()
+    .route(Method::Get, "/", |request| match requested(request) {
+      Ok((workout, timeline)) => web::html(squat_web_html(&timeline, &workout)),
+      Err(err) => web::text(400, format!("invalid workout: {:#}", err)),
+    })
+    .route(Method::Get, "/api/config", |request| {
+      match requested(request) {
+        Ok((workout, timeline)) => web::json(200, workout.to_json(&timeline)),
+        Err(err) => web::json(
+          400,
+          serde_json::json!({ "error": format!("{:#}", err) }).to_string(),
+        ),
+      }
+    })
+    .route(Method::Get, "/view", |_| web::html(SQUAT_VIEW_HTML))
+    .route(Method::Get, "/remote", |_| web::html(SQUAT_REMOTE_HTML))
+    .route(Method::Post, "/api/control", |request| {
+      let remote = requested(request).and_then(|(workout, timeline)| {
+        read_json::<RemoteRequest>(request).map(|remote| (workout, timeline, remote))
+      });
+      let applied = remote.and_then(|(workout, timeline, remote)| {
+        tracing::info!("session command: {:?}", remote.command);
+        let started = SessionWorkout {
+          title: workout.title,
+          query: web::query_without(web::request_query(request), SESSION_PARAM),
+          timeline,
+        };
+        sessions.apply(client(request).as_deref(), remote.command, started)
+      });
+      match applied {
+        Ok(()) => web::no_content(),
+        Err(err) => web::text(400, format!("invalid session command: {:#}", err)),
+      }
+    })
+    .route(Method::Post, "/api/state", |request| {
+      let published = read_json::<LiveState>(request)
+        .and_then(|state| sessions.publish_state(client(request).as_deref(), &state));
+      match published {
+        Ok(()) => web::no_content(),
+        Err(err) => web::text(400, format!("invalid live state: {:#}", err)),
+      }
+    })
+    .route(Method::Get, "/api/clock", |_| {
+      web::json(
+        200,
+        serde_json::json!({ "now": sessions.now_ms() }).to_string(),
+      )
+    })
+    .route(Method::Get, "/api/session", |request| {
+      match sessions.snapshot(client(request).as_deref()) {
+        Ok((state, progress)) => {
+          let body = serde_json::json!({
+            "now": sessions.now_ms(),
+            "session": state,
+            "progress": progress,
+          });
+          web::json(200, body.to_string())
+        }
+        Err(err) => web::json(
+          400,
+          serde_json::json!({ "error": format!("{:#}", err) }).to_string(),
+        ),
+      }
+    })
+    .stream(Method::Get, "/api/events", |request| {
+      let id = client(&request);
+      match EventStream::open(request) {
+        Ok(stream) => {
+          if let Err(err) = sessions.serve(id.as_deref(), stream) {
+            tracing::debug!("event stream refused: {:#}", err);
+          }
+        }
+        Err(err) => tracing::debug!("event stream closed before it started: {}", err),
+      }
+    });
+
+  // One thread per request: event streams stay open for as long as a viewer is connected.
+  notifier.ready(&format!("listening on {}", listening));
+  thread::scope(|scope| {
+    let result = loop {
+      if let Some(signal) = shutdown.requested() {
+        tracing::info!("received {}, shutting down", signal.name());
+        break Ok(());
+      }
+      match server.recv_timeout(Duration::from_millis(200)) {
+        Ok(Some(request)) => {
+          let router = &router;
+          scope.spawn(move || router.handle(request));
+        }
+        Ok(None) => {}
+        Err(err) => break Err(err.into()),
+      }
+      sessions.poll();
+      notifier.watchdog();
+    };
+    notifier.stopping();
+    sessions.shut_down();
+    result
+  })
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(request: &mut Request) -> Result<T> {
+  let mut body = String::new();
+  request
+    .as_reader()
+    .take(MAX_REQUEST_BYTES)
+    .read_to_string(&mut body)?;
+  Ok(serde_json::from_str(&body)?)
+}
+
+fn run_plan(args: RunArgs) -> Result<()> {
+  let plan = WorkoutPlan::load(&args.plan)?;
+  let timeline = plan.timeline()?;
+  tracing::info!(
+    "plan {:?}: {} sets, {} reps",
+    plan.title(),
+    timeline.sets().len(),
+    timeline.total_reps()
+  );
+  if args.web {
+    let workout = args
+      .web_args
+      .workout(plan.title(), WorkoutSource::Plan(timeline));
+    serve_web(&workout, &args.web_args.addr)
+  } else {
+    play_terminal(
+      &timeline,
+      &args.terminal,
+      args.web_args.tremor.tremor(),
+      plan.title(),
+      None,
+    )
+  }
+}
+
+fn run_history(args: HistoryArgs) -> Result<()> {
+  let mut records = args.filter.load()?;
+  if let Some(limit) = args.limit {
+    let skip = records.len().saturating_sub(limit);
+    records.drain(..skip);
+  }
+  if args.json {
+    println!("{}", serde_json::to_string_pretty(&records)?);
+  } else {
+    print!("{}", history::format_table(&records));
+  }
+  Ok(())
+}
 
+fn run_stats(args: StatsArgs) -> Result<()> {
+  let records = args.filter.load()?;
+  let stats = Stats::compute(&records, Local::now().date_naive(), args.weeks);
+  if args.json {
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+  } else {
+    print!("{}", stats.format_table());
+  }
   Ok(())
 }
 
+fn run_service(args: ServiceArgs) -> Result<()> {
+  match args.action {
+    ServiceAction::Install(args) => {
+      let dry_run = args.target.dry_run;
+      args.unit()?.install(dry_run)
+    }
+    ServiceAction::Print(args) => {
+      let unit = args.unit()?;
+      if unit.socket.is_some() {
+        for (path, content) in unit.files()? {
+          println!("# {}", path.display());
+          print!("{}", content);
+        }
+      } else {
+        print!("{}", unit.render());
+      }
+      Ok(())
+    }
+    ServiceAction::Uninstall(args) => service::uninstall(args.scope, &args.name, args.dry_run),
+  }
+}
+
 fn main() -> Result<()> {
   color_eyre::install()?;
   init_tracing()?;
@@ -2732,6 +4757,10 @@ fn main() -> Result<()> {
   match cli.command {
     Commands::Squat(args) => run_squat(args),
     Commands::SquatWeb(args) => run_squat_web(args),
+    Commands::Run(args) => run_plan(args),
+    Commands::History(args) => run_history(args),
+    Commands::Stats(args) => run_stats(args),
+    Commands::Service(args) => run_service(args),
   }
 }
 
@@ -2739,4 +4768,162 @@ fn main() -> Result<()> {
 
 /* test for pri ******************************************************************************************/
 
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+  use crate::timeline::Tempo;
+
+  #[test]
+  fn fill_placeholders_leaves_placeholders_in_values() {
+    let html = fill_placeholders(
+      "<h1>__EXERCISE__</h1><p>v__VERSION__</p>let config = __CONFIG__;",
+      &[
+        ("__EXERCISE__", "__CONFIG__"),
+        ("__VERSION__", "1.0"),
+        ("__CONFIG__", "{}"),
+      ],
+    );
+    assert_eq!(html, "<h1>__CONFIG__</h1><p>v1.0</p>let config = {};");
+    assert_eq!(
+      fill_placeholders("a___SETS__b", &[("__SETS__", "2")]),
+      "a_2b"
+    );
+  }
+
+  #[test]
+  fn html_escape_escapes_markup() {
+    assert_eq!(
+      html_escape(r#"</script><b a="x">Tom & 'Jerry'"#),
+      "&lt;/script&gt;&lt;b a=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;"
+    );
+    assert_eq!(html_escape("スクワット"), "スクワット");
+  }
+
+  fn frame_at(timeline: &WorkoutTimeline, secs: f64, paused: bool) -> FrameState {
+    frame_state(&timeline.at(Duration::from_secs_f64(secs)), paused, 0.0)
+  }
+
+  fn event_names(events: &[(WorkoutEvent, &FrameState)]) -> Vec<WorkoutEvent> {
+    events.iter().map(|(event, _)| *event).collect()
+  }
+
+  #[test]
+  fn frame_events_keeps_transitions_on_pause_and_resume() {
+    let tempo = Tempo {
+      down: 1.0,
+      hold: 1.0,
+      up: 1.0,
+    };
+    let timeline = WorkoutTimeline::uniform(tempo, 2, 2, Duration::from_secs(2));
+    // DOWN of rep 1 -> HOLD, paused in the same frame.
+    let down = frame_at(&timeline, 0.5, false);
+    let hold = frame_at(&timeline, 1.5, true);
+    assert_eq!(
+      event_names(&frame_events(Some(&down), &hold)),
+      [WorkoutEvent::Phase, WorkoutEvent::Pause]
+    );
+    // UP of the last rep -> REST, resumed in the same frame.
+    let up = frame_at(&timeline, 5.5, true);
+    let rest = frame_at(&timeline, 6.5, false);
+    let events = frame_events(Some(&up), &rest);
+    assert_eq!(
+      event_names(&events),
+      [
+        WorkoutEvent::SetComplete,
+        WorkoutEvent::Phase,
+        WorkoutEvent::Resume
+      ]
+    );
+    assert_eq!(frame_cue(&events), Some(Cue::SetComplete));
+    // A pause alone is just the pause.
+    let paused = frame_at(&timeline, 0.5, true);
+    assert_eq!(
+      event_names(&frame_events(Some(&down), &paused)),
+      [WorkoutEvent::Pause]
+    );
+  }
+
+  /// Everything a plain console printed.
+  #[derive(Clone, Default)]
+  struct Printed(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+  impl Write for Printed {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.0.lock().unwrap().extend_from_slice(buf);
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn plain_console_prints_one_line_per_change() {
+    let tempo = Tempo {
+      down: 1.0,
+      hold: 1.0,
+      up: 1.0,
+    };
+    let timeline = WorkoutTimeline::uniform(tempo, 2, 2, Duration::from_secs(2));
+    let printed = Printed::default();
+    let (_commands, receiver) = mpsc::channel();
+    let mut console = PlainConsole::with_output(receiver, Box::new(printed.clone()));
+
+    // 50 fps through the whole workout, paused for a while during the first HOLD.
+    let mut secs = 0.0;
+    while secs < timeline.total().as_secs_f64() {
+      let paused = (1.4..1.6).contains(&secs);
+      console.frame(&frame_at(&timeline, secs, paused)).unwrap();
+      secs += 0.02;
+    }
+    console.line("Complete! Reps: 4/4").unwrap();
+
+    let text = String::from_utf8(printed.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(
+      text.lines().collect::<Vec<_>>(),
+      [
+        "Slow Squat  Set 1/2",
+        "Rep 1/2 DOWN 1.0s",
+        "Rep 1/2 HOLD 1.0s",
+        "PAUSED",
+        "RESUMED",
+        "Rep 1/2 UP 1.0s",
+        "Rep 2/2 DOWN 1.0s",
+        "Rep 2/2 HOLD 1.0s",
+        "Rep 2/2 UP 1.0s",
+        "REST 2s",
+        "Slow Squat  Set 2/2",
+        "Rep 1/2 DOWN 1.0s",
+        "Rep 1/2 HOLD 1.0s",
+        "Rep 1/2 UP 1.0s",
+        "Rep 2/2 DOWN 1.0s",
+        "Rep 2/2 HOLD 1.0s",
+        "Rep 2/2 UP 1.0s",
+        "Complete! Reps: 4/4",
+      ]
+    );
+  }
+
+  #[test]
+  fn plain_command_parses_stdin_lines() {
+    for (line, action) in [
+      ("", InputAction::TogglePause),
+      ("p", InputAction::TogglePause),
+      (" SPACE ", InputAction::TogglePause),
+      ("pause", InputAction::Pause),
+      ("r", InputAction::Resume),
+      ("Resume\r", InputAction::Resume),
+      ("q", InputAction::Exit),
+      ("quit", InputAction::Exit),
+      ("stop", InputAction::Exit),
+      ("exit", InputAction::Exit),
+    ] {
+      assert_eq!(plain_command(line), Some(action), "{:?}", line);
+    }
+    assert_eq!(plain_command("skip"), None);
+    assert_eq!(plain_command("pa use"), None);
+  }
+}
+
 /* test for pub ******************************************************************************************/
@@ -0,0 +1,385 @@
+/* use  **************************************************************************************************/
+
+use crate::timeline::{DEFAULT_EXERCISE, HOLD_SECS, SetSpec, Tempo, WorkoutTimeline};
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use serde::Deserialize;
+use std::{fs, path::Path, time::Duration};
+
+/* enum  *************************************************************************************************/
+
+/// What a block is for; warm-up and cool-down sets are labelled as such.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockKind {
+  Warmup,
+  #[default]
+  Main,
+  Cooldown,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// A workout session loaded from a TOML plan file.
+///
+/// ```toml
+/// name = "Leg day"
+/// rest = 90            # between blocks
+///
+/// [warmup]
+/// count = 5
+/// tempo = "2-1-2"
+///
+/// [[block]]
+/// exercise = "Slow Squat"
+/// count = 10
+/// tempo = "4-2-1"
+/// sets = 3
+/// rest = 60            # between sets
+///
+/// [[block]]
+/// kind = "cooldown"    # "warmup", "main" (default) or "cooldown"
+/// exercise = "Lunge"
+/// count = 5
+/// tempo = "3-1-3"
+///
+/// [cooldown]
+/// count = 5
+/// duration = 60
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WorkoutPlan {
+  pub name: Option<String>,
+  #[serde(default)]
+  pub rest: u64,
+  pub warmup: Option<PlanBlock>,
+  #[serde(default, rename = "block")]
+  pub blocks: Vec<PlanBlock>,
+  pub cooldown: Option<PlanBlock>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PlanBlock {
+  /// Implied by `[warmup]` and `[cooldown]`; `[[block]]` entries default to `main`.
+  pub kind: Option<BlockKind>,
+  pub exercise: Option<String>,
+  pub count: u32,
+  #[serde(default = "default_sets")]
+  pub sets: u32,
+  #[serde(default)]
+  pub rest: u64,
+  pub rest_after: Option<u64>,
+  pub duration: Option<u64>,
+  pub hold: Option<f64>,
+  pub down: Option<f64>,
+  pub up: Option<f64>,
+  pub tempo: Option<String>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl BlockKind {
+  fn name(self) -> &'static str {
+    match self {
+      BlockKind::Warmup => "warmup",
+      BlockKind::Main => "main",
+      BlockKind::Cooldown => "cooldown",
+    }
+  }
+}
+
+impl WorkoutPlan {
+  pub fn load(path: &Path) -> Result<Self> {
+    let text = fs::read_to_string(path)
+      .wrap_err_with(|| format!("failed to read plan {}", path.display()))?;
+    let plan: Self =
+      toml::from_str(&text).wrap_err_with(|| format!("invalid plan {}", path.display()))?;
+    plan
+      .validate()
+      .wrap_err_with(|| format!("invalid plan {}", path.display()))?;
+    Ok(plan)
+  }
+
+  pub fn title(&self) -> &str {
+    self.name.as_deref().unwrap_or(DEFAULT_EXERCISE)
+  }
+
+  fn labeled_blocks(&self) -> Vec<(String, BlockKind, &PlanBlock)> {
+    let mut blocks = Vec::new();
+    if let Some(block) = &self.warmup {
+      blocks.push(("warmup".to_string(), BlockKind::Warmup, block));
+    }
+    for (idx, block) in self.blocks.iter().enumerate() {
+      let kind = block.kind.unwrap_or_default();
+      blocks.push((format!("block {}", idx + 1), kind, block));
+    }
+    if let Some(block) = &self.cooldown {
+      blocks.push(("cooldown".to_string(), BlockKind::Cooldown, block));
+    }
+    blocks
+  }
+
+  pub fn validate(&self) -> Result<()> {
+    if self.warmup.is_none() && self.blocks.is_empty() && self.cooldown.is_none() {
+      return Err(eyre!("plan has no blocks; add at least one [[block]]"));
+    }
+    for (label, kind, block) in self.labeled_blocks() {
+      if let Some(declared) = block.kind.filter(|declared| *declared != kind) {
+        return Err(eyre!(
+          "{} ({}): kind = \"{}\" does not belong in [{}]",
+          label,
+          block.exercise(),
+          declared.name(),
+          kind.name()
+        ));
+      }
+      block
+        .tempo()
+        .wrap_err_with(|| format!("{} ({})", label, block.exercise()))?;
+    }
+    Ok(())
+  }
+
+  pub fn timeline(&self) -> Result<WorkoutTimeline> {
+    let blocks = self.labeled_blocks();
+    let mut specs = Vec::new();
+    for (idx, (label, kind, block)) in blocks.iter().enumerate() {
+      let tempo = block
+        .tempo()
+        .wrap_err_with(|| format!("{} ({})", label, block.exercise()))?;
+      let exercise = match kind {
+        BlockKind::Warmup => format!("Warm-up: {}", block.exercise()),
+        BlockKind::Main => block.exercise().to_string(),
+        BlockKind::Cooldown => format!("Cool-down: {}", block.exercise()),
+      };
+      let is_last_block = idx + 1 == blocks.len();
+      for set in 0..block.sets {
+        let rest_after = if set + 1 < block.sets {
+          block.rest
+        } else if is_last_block {
+          0
+        } else {
+          block.rest_after.unwrap_or(self.rest)
+        };
+        specs.push(SetSpec {
+          exercise: exercise.clone(),
+          reps: block.count,
+          tempo,
+          rest_after: Duration::from_secs(rest_after),
+        });
+      }
+    }
+    Ok(WorkoutTimeline::new(&specs))
+  }
+}
+
+impl PlanBlock {
+  pub fn exercise(&self) -> &str {
+    self.exercise.as_deref().unwrap_or(DEFAULT_EXERCISE)
+  }
+
+  fn tempo(&self) -> Result<Tempo> {
+    if self.count == 0 {
+      return Err(eyre!("count must be >= 1"));
+    }
+    if self.sets == 0 {
+      return Err(eyre!("sets must be >= 1"));
+    }
+    if let Some(spec) = &self.tempo {
+      if self.hold.is_some() || self.down.is_some() || self.up.is_some() {
+        return Err(eyre!("tempo cannot be combined with hold, down or up"));
+      }
+      return spec.parse::<Tempo>().map_err(|err| eyre!(err));
+    }
+    let hold = self.hold.unwrap_or(HOLD_SECS);
+    let rep_secs = match (self.duration, self.down, self.up) {
+      (Some(_), Some(_), Some(_)) => {
+        return Err(eyre!(
+          "duration cannot be combined with both down and up; drop one of them"
+        ));
+      }
+      (Some(duration), _, _) => duration as f64 / self.count as f64,
+      (None, Some(down), Some(up)) => down + hold + up,
+      (None, _, _) => {
+        return Err(eyre!(
+          "set duration, tempo, or both down and up to define the rep length"
+        ));
+      }
+    };
+    Tempo::from_rep_partial(rep_secs, hold, self.down, self.up)
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+fn default_sets() -> u32 {
+  1
+}
+
+/* test for pri ******************************************************************************************/
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  fn plan(text: &str) -> WorkoutPlan {
+    toml::from_str(text).unwrap()
+  }
+
+  fn error(text: &str) -> String {
+    format!("{:#}", plan(text).validate().unwrap_err())
+  }
+
+  /// `(exercise, reps, rest before the next set)` for every set of the plan.
+  fn sets(plan: &WorkoutPlan) -> Vec<(String, u32, f64)> {
+    let timeline = plan.timeline().unwrap();
+    let spans = timeline.sets();
+    spans
+      .iter()
+      .enumerate()
+      .map(|(idx, span)| {
+        let rest = spans
+          .get(idx + 1)
+          .map_or(0.0, |next| next.start - span.start - span.duration);
+        (span.exercise.clone(), span.reps, rest)
+      })
+      .collect()
+  }
+
+  const LEG_DAY: &str = r#"
+    name = "Leg day"
+    rest = 90
+
+    [warmup]
+    count = 2
+    tempo = "1-1-1"
+
+    [[block]]
+    exercise = "Slow Squat"
+    count = 3
+    tempo = "4-2-1"
+    sets = 2
+    rest = 60
+
+    [[block]]
+    exercise = "Lunge"
+    count = 2
+    duration = 12
+    hold = 2
+    rest_after = 30
+
+    [cooldown]
+    count = 1
+    down = 2
+    up = 2
+  "#;
+
+  #[test]
+  fn parse_reads_blocks_and_defaults() {
+    let plan = plan(LEG_DAY);
+    assert_eq!(plan.title(), "Leg day");
+    assert_eq!(plan.rest, 90);
+    assert_eq!(plan.blocks.len(), 2);
+    assert_eq!(plan.blocks[0].sets, 2);
+    assert_eq!(plan.blocks[1].sets, 1);
+    assert_eq!(plan.blocks[1].rest, 0);
+    assert_eq!(plan.blocks[1].kind, None);
+    assert!(plan.validate().is_ok());
+  }
+
+  #[test]
+  fn parse_rejects_unknown_fields() {
+    assert!(toml::from_str::<WorkoutPlan>("[[block]]\ncount = 1\nreps = 2\n").is_err());
+    assert!(toml::from_str::<WorkoutPlan>("[[block]]\ncount = 1\nkind = \"stretch\"\n").is_err());
+  }
+
+  #[test]
+  fn timeline_places_rests_between_sets_and_blocks() {
+    assert_eq!(
+      sets(&plan(LEG_DAY)),
+      [
+        ("Warm-up: Slow Squat".to_string(), 2, 90.0),
+        ("Slow Squat".to_string(), 3, 60.0),
+        ("Slow Squat".to_string(), 3, 90.0),
+        ("Lunge".to_string(), 2, 30.0),
+        ("Cool-down: Slow Squat".to_string(), 1, 0.0),
+      ]
+    );
+  }
+
+  #[test]
+  fn timeline_labels_block_kinds() {
+    let plan = plan(
+      r#"
+      [[block]]
+      kind = "warmup"
+      exercise = "March"
+      count = 1
+      tempo = "1-1-1"
+
+      [[block]]
+      exercise = "Squat"
+      count = 1
+      tempo = "1-1-1"
+
+      [[block]]
+      kind = "cooldown"
+      exercise = "Stretch"
+      count = 1
+      tempo = "1-1-1"
+    "#,
+    );
+    let names: Vec<String> = sets(&plan).into_iter().map(|set| set.0).collect();
+    assert_eq!(names, ["Warm-up: March", "Squat", "Cool-down: Stretch"]);
+  }
+
+  #[test]
+  fn validate_names_the_offending_block() {
+    let message = error(
+      r#"
+      [[block]]
+      count = 2
+      tempo = "1-1-1"
+
+      [[block]]
+      exercise = "Lunge"
+      count = 0
+      tempo = "1-1-1"
+    "#,
+    );
+    assert_eq!(message, "block 2 (Lunge): count must be >= 1");
+    let message = error("[warmup]\ncount = 2\ntempo = \"1-1-1\"\nhold = 2\n");
+    assert_eq!(
+      message,
+      "warmup (Slow Squat): tempo cannot be combined with hold, down or up"
+    );
+    let message = error("[cooldown]\ncount = 2\nkind = \"warmup\"\ntempo = \"1-1-1\"\n");
+    assert_eq!(
+      message,
+      "cooldown (Slow Squat): kind = \"warmup\" does not belong in [cooldown]"
+    );
+  }
+
+  #[test]
+  fn validate_rejects_duration_with_down_and_up() {
+    let message = error("[[block]]\ncount = 2\nduration = 10\ndown = 2\nup = 2\n");
+    assert_eq!(
+      message,
+      "block 1 (Slow Squat): duration cannot be combined with both down and up; drop one of them"
+    );
+  }
+
+  #[test]
+  fn validate_requires_a_block_and_a_rep_length() {
+    assert_eq!(
+      error("name = \"Empty\"\n"),
+      "plan has no blocks; add at least one [[block]]"
+    );
+    assert!(
+      error("[[block]]\ncount = 2\nhold = 2\n").starts_with("block 1 (Slow Squat): set duration")
+    );
+  }
+}
@@ -0,0 +1,426 @@
+/* use  **************************************************************************************************/
+
+use crossterm::{
+  cursor::MoveTo,
+  queue,
+  style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+  terminal::{self, BeginSynchronizedUpdate, Clear, ClearType, EndSynchronizedUpdate},
+};
+use std::{
+  io::{self, Write},
+  sync::atomic::{AtomicU64, Ordering},
+  time::{Duration, Instant},
+};
+use unicode_width::UnicodeWidthChar;
+
+/* global const  *****************************************************************************************/
+
+/// Used when the terminal can't report its size.
+const FALLBACK_SIZE: (u16, u16) = (80, 24);
+/// Unchanged cells between two changes that are rewritten rather than skipped with a
+/// cursor move, which costs about as many bytes.
+const MERGE_GAP: usize = 6;
+const BLANK: Cell = Cell {
+  glyph: Glyph::Char(' '),
+  style: Style {
+    fg: None,
+    bold: false,
+  },
+};
+/// Redraws everything this often anyway, in case something else wrote to the terminal
+/// without telling us.
+const REPAINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Bumped by `foreign_output`; a change since the last frame means the screen no longer
+/// matches `Renderer::previous`.
+static FOREIGN_WRITES: AtomicU64 = AtomicU64::new(0);
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Glyph {
+  Char(char),
+  /// Right half of a double-width glyph in the cell to the left.
+  Continuation,
+}
+
+/* struct  ***********************************************************************************************/
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+  pub fg: Option<Color>,
+  pub bold: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Span {
+  text: String,
+  style: Style,
+}
+
+/// One row of a frame, made of differently styled pieces.
+#[derive(Clone, Debug, Default)]
+pub struct Line {
+  spans: Vec<Span>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cell {
+  glyph: Glyph,
+  style: Style,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Grid {
+  width: usize,
+  height: usize,
+  cells: Vec<Cell>,
+}
+
+/// Draws whole frames given as lines, but only sends the cells that differ from the
+/// previous frame, inside a synchronized update so the terminal never shows half a frame.
+pub struct Renderer {
+  out: io::Stdout,
+  previous: Option<Grid>,
+  /// `FOREIGN_WRITES` when `previous` was drawn.
+  foreign_writes: u64,
+  /// When the screen was last cleared and drawn in full.
+  repainted_at: Instant,
+}
+
+/* impl standard traits  *********************************************************************************/
+
+impl From<String> for Line {
+  fn from(text: String) -> Self {
+    Line::default().push(text, Style::default())
+  }
+}
+
+impl From<&str> for Line {
+  fn from(text: &str) -> Self {
+    Line::from(text.to_string())
+  }
+}
+
+impl Drop for Renderer {
+  /// Leaves the cursor under the last frame, so whatever the shell prints next doesn't
+  /// land on top of it.
+  fn drop(&mut self) {
+    let Some(previous) = &self.previous else {
+      return;
+    };
+    let below = (0..previous.height)
+      .rposition(|y| {
+        previous
+          .row(y)
+          .iter()
+          .any(|cell| cell.glyph != Glyph::Char(' '))
+      })
+      .map_or(0, |y| y + 1);
+    let _ = queue!(self.out, MoveTo(0, below as u16));
+    let _ = self.out.flush();
+  }
+}
+
+/* impl  *************************************************************************************************/
+
+impl Line {
+  pub fn push(mut self, text: impl Into<String>, style: Style) -> Self {
+    self.spans.push(Span {
+      text: text.into(),
+      style,
+    });
+    self
+  }
+
+  pub fn push_line(mut self, line: Line) -> Self {
+    self.spans.extend(line.spans);
+    self
+  }
+}
+
+impl Grid {
+  /// Lays `lines` out from the top-left corner; what doesn't fit is cut off.
+  fn new((width, height): (u16, u16), lines: &[Line]) -> Self {
+    let (width, height) = (width as usize, height as usize);
+    let mut cells = vec![BLANK; width * height];
+    for (y, line) in lines.iter().take(height).enumerate() {
+      let row = &mut cells[y * width..(y + 1) * width];
+      let mut x = 0;
+      let chars = line
+        .spans
+        .iter()
+        .flat_map(|span| span.text.chars().map(move |ch| (ch, span.style)));
+      for (ch, style) in chars {
+        let w = ch.width().unwrap_or(0);
+        if w == 0 {
+          continue;
+        }
+        if x + w > width {
+          break;
+        }
+        row[x] = Cell {
+          glyph: Glyph::Char(ch),
+          style,
+        };
+        if w == 2 {
+          row[x + 1] = Cell {
+            glyph: Glyph::Continuation,
+            style,
+          };
+        }
+        x += w;
+      }
+    }
+    Self {
+      width,
+      height,
+      cells,
+    }
+  }
+
+  fn row(&self, y: usize) -> &[Cell] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+}
+
+impl Renderer {
+  pub fn new() -> Self {
+    Self {
+      out: io::stdout(),
+      previous: None,
+      foreign_writes: FOREIGN_WRITES.load(Ordering::Relaxed),
+      repainted_at: Instant::now(),
+    }
+  }
+
+  /// Writes nothing when the frame looks the same as the last one.
+  pub fn draw(&mut self, lines: &[Line]) -> io::Result<()> {
+    let size = terminal::size().unwrap_or(FALLBACK_SIZE);
+    let next = Grid::new(size, lines);
+    let foreign_writes = FOREIGN_WRITES.load(Ordering::Relaxed);
+    if self.stale(foreign_writes, Instant::now()) {
+      // Diffing against what we drew would leave the other output's leftovers on screen.
+      self.previous = None;
+    }
+    self.foreign_writes = foreign_writes;
+    if self.previous.as_ref() == Some(&next) {
+      return Ok(());
+    }
+
+    let mut buf = Vec::new();
+    let mut style = Style::default();
+    queue!(buf, BeginSynchronizedUpdate, SetAttribute(Attribute::Reset))?;
+    match &self.previous {
+      Some(previous) if previous.width == next.width && previous.height == next.height => {
+        for y in 0..next.height {
+          let (old, new) = (previous.row(y), next.row(y));
+          for (start, end) in changed_runs(old, new) {
+            queue!(buf, MoveTo(start as u16, y as u16))?;
+            print_cells(&mut buf, &new[start..end], &mut style)?;
+          }
+        }
+      }
+      // First frame, a resized terminal or a stale screen: start from a clean screen.
+      _ => {
+        self.repainted_at = Instant::now();
+        queue!(buf, Clear(ClearType::All))?;
+        for y in 0..next.height {
+          let row = next.row(y);
+          let end = row
+            .iter()
+            .rposition(|cell| *cell != BLANK)
+            .map_or(0, |x| x + 1);
+          if end > 0 {
+            queue!(buf, MoveTo(0, y as u16))?;
+            print_cells(&mut buf, &row[..end], &mut style)?;
+          }
+        }
+      }
+    }
+    if style != Style::default() {
+      queue!(buf, SetAttribute(Attribute::Reset))?;
+    }
+    queue!(buf, EndSynchronizedUpdate)?;
+    // One write per frame keeps it to a single packet over SSH.
+    self.out.write_all(&buf)?;
+    self.out.flush()?;
+    self.previous = Some(next);
+    Ok(())
+  }
+
+  fn stale(&self, foreign_writes: u64, now: Instant) -> bool {
+    foreign_writes != self.foreign_writes
+      || now.duration_since(self.repainted_at) >= REPAINT_INTERVAL
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// Tells renderers that something else, like a log line, wrote to the terminal; the next
+/// frame is drawn in full.
+pub fn foreign_output() {
+  FOREIGN_WRITES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// `[start, end)` column ranges to rewrite; never starts or ends inside a wide glyph of
+/// either frame.
+fn changed_runs(old: &[Cell], new: &[Cell]) -> Vec<(usize, usize)> {
+  let split = |x: usize| old[x].glyph == Glyph::Continuation || new[x].glyph == Glyph::Continuation;
+  let mut runs: Vec<(usize, usize)> = Vec::new();
+  for x in 0..new.len() {
+    if old[x] == new[x] {
+      continue;
+    }
+    let mut start = x;
+    while start > 0 && split(start) {
+      start -= 1;
+    }
+    let mut end = x + 1;
+    while end < new.len() && split(end) {
+      end += 1;
+    }
+    match runs.last_mut() {
+      Some(last) if start <= last.1 + MERGE_GAP => last.1 = last.1.max(end),
+      _ => runs.push((start, end)),
+    }
+  }
+  runs
+}
+
+/// Prints `cells` from the cursor, switching attributes only where the style changes.
+fn print_cells(buf: &mut Vec<u8>, cells: &[Cell], current: &mut Style) -> io::Result<()> {
+  for cell in cells {
+    let Glyph::Char(ch) = cell.glyph else {
+      continue;
+    };
+    // Spaces only show the background, which themes leave alone.
+    if cell.style != *current && ch != ' ' {
+      queue!(buf, SetAttribute(Attribute::Reset))?;
+      match cell.style.fg.and_then(basic_sgr) {
+        Some(code) => queue!(buf, Print(format!("\x1b[{}m", code)))?,
+        None => {
+          if let Some(fg) = cell.style.fg {
+            queue!(buf, SetForegroundColor(fg))?;
+          }
+        }
+      }
+      if cell.style.bold {
+        queue!(buf, SetAttribute(Attribute::Bold))?;
+      }
+      *current = cell.style;
+    }
+    queue!(buf, Print(ch))?;
+  }
+  Ok(())
+}
+
+/// crossterm writes even the 16 standard colors as `38;5;N`, which 16-color terminals
+/// may not know; these are the classic codes for them.
+fn basic_sgr(color: Color) -> Option<u8> {
+  let code = match color {
+    Color::Black => 30,
+    Color::DarkRed => 31,
+    Color::DarkGreen => 32,
+    Color::DarkYellow => 33,
+    Color::DarkBlue => 34,
+    Color::DarkMagenta => 35,
+    Color::DarkCyan => 36,
+    Color::Grey => 37,
+    Color::DarkGrey => 90,
+    Color::Red => 91,
+    Color::Green => 92,
+    Color::Yellow => 93,
+    Color::Blue => 94,
+    Color::Magenta => 95,
+    Color::Cyan => 96,
+    Color::White => 97,
+    _ => return None,
+  };
+  Some(code)
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  fn row(width: u16, text: &str) -> Vec<Cell> {
+    Grid::new((width, 1), &[Line::from(text)]).cells
+  }
+
+  fn glyphs(cells: &[Cell]) -> Vec<Glyph> {
+    cells.iter().map(|cell| cell.glyph).collect()
+  }
+
+  #[test]
+  fn grid_gives_wide_glyphs_two_cells() {
+    let grid = Grid::new((6, 2), &[Line::from("a漢b"), Line::from("字")]);
+    assert_eq!(
+      glyphs(grid.row(0)),
+      [
+        Glyph::Char('a'),
+        Glyph::Char('漢'),
+        Glyph::Continuation,
+        Glyph::Char('b'),
+        Glyph::Char(' '),
+        Glyph::Char(' '),
+      ]
+    );
+    assert_eq!(
+      glyphs(&grid.row(1)[..3]),
+      [Glyph::Char('字'), Glyph::Continuation, Glyph::Char(' ')]
+    );
+  }
+
+  #[test]
+  fn grid_cuts_a_wide_glyph_that_does_not_fit() {
+    assert_eq!(
+      glyphs(&row(3, "ab漢")),
+      [Glyph::Char('a'), Glyph::Char('b'), Glyph::Char(' ')]
+    );
+    // Combining marks have no width and are dropped rather than shifting the row.
+    assert_eq!(
+      glyphs(&row(3, "e\u{301}x")),
+      [Glyph::Char('e'), Glyph::Char('x'), Glyph::Char(' ')]
+    );
+  }
+
+  #[test]
+  fn grid_cuts_extra_lines() {
+    let grid = Grid::new((2, 1), &[Line::from("ab"), Line::from("cd")]);
+    assert_eq!(glyphs(&grid.cells), [Glyph::Char('a'), Glyph::Char('b')]);
+  }
+
+  #[test]
+  fn changed_runs_merges_small_gaps() {
+    let old = row(20, "");
+    // Six unchanged cells between two changes: cheaper to rewrite than to skip.
+    assert_eq!(changed_runs(&old, &row(20, "x      y")), [(0, 8)]);
+    // Seven: two runs.
+    assert_eq!(changed_runs(&old, &row(20, "x       y")), [(0, 1), (8, 9)]);
+    assert_eq!(changed_runs(&old, &old), []);
+  }
+
+  #[test]
+  fn changed_runs_covers_whole_wide_glyphs() {
+    // A wide glyph replaced by two narrow ones rewrites just those two cells.
+    assert_eq!(changed_runs(&row(4, "漢x"), &row(4, "abx")), [(0, 2)]);
+    // A new wide glyph is written whole, right half included.
+    assert_eq!(changed_runs(&row(4, "ab c"), &row(4, "a漢c")), [(1, 3)]);
+    // A wide glyph shifted by one column covers both old and new halves.
+    assert_eq!(changed_runs(&row(4, "漢x"), &row(4, "a漢")), [(0, 3)]);
+    assert_eq!(changed_runs(&row(4, "x漢"), &row(4, "x字")), [(1, 3)]);
+  }
+
+  #[test]
+  fn renderer_repaints_after_foreign_output_and_on_an_interval() {
+    let renderer = Renderer::new();
+    let now = renderer.repainted_at;
+    assert!(!renderer.stale(renderer.foreign_writes, now));
+    assert!(renderer.stale(renderer.foreign_writes + 1, now));
+    assert!(!renderer.stale(renderer.foreign_writes, now + REPAINT_INTERVAL / 2));
+    assert!(renderer.stale(renderer.foreign_writes, now + REPAINT_INTERVAL));
+  }
+}
@@ -0,0 +1,480 @@
+/* use  **************************************************************************************************/
+
+use crate::dirs;
+use clap::ValueEnum;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use std::{fmt::Write as _, fs, path::PathBuf, process::Command};
+
+/* global const  *****************************************************************************************/
+
+pub const DEFAULT_SERVICE_NAME: &str = "trainer-squat-web";
+/// systemd restarts the service when the accept loop stops pinging for this long.
+const WATCHDOG_SECS: u64 = 30;
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ServiceScope {
+  /// `~/.config/systemd/user`, managed with `systemctl --user`
+  User,
+  /// `/etc/systemd/system`, needs root
+  System,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// A systemd unit that runs one `trainer` serve command.
+#[derive(Debug)]
+pub struct ServiceUnit {
+  pub name: String,
+  pub scope: ServiceScope,
+  pub bin: PathBuf,
+  /// Arguments after the binary, already checked against the CLI definition.
+  pub args: Vec<String>,
+  pub run_as: Option<String>,
+  pub run_group: Option<String>,
+  pub workdir: Option<PathBuf>,
+  pub hardening: bool,
+  /// `ListenStream=` of a companion `.socket` unit that starts the service on demand.
+  pub socket: Option<String>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl ServiceScope {
+  fn wanted_by(self) -> &'static str {
+    match self {
+      ServiceScope::User => "default.target",
+      ServiceScope::System => "multi-user.target",
+    }
+  }
+
+  fn systemctl(self) -> Command {
+    let mut command = Command::new("systemctl");
+    if self == ServiceScope::User {
+      command.arg("--user");
+    }
+    command
+  }
+
+  fn systemctl_line(self, args: &[&str]) -> String {
+    let user = if self == ServiceScope::User {
+      " --user"
+    } else {
+      ""
+    };
+    format!("systemctl{} {}", user, args.join(" "))
+  }
+}
+
+impl ServiceUnit {
+  pub fn render(&self) -> String {
+    let mut out = String::new();
+    let exec = std::iter::once(self.bin.to_string_lossy().into_owned())
+      .chain(self.args.iter().cloned())
+      .map(|arg| quote(&arg))
+      .collect::<Vec<_>>()
+      .join(" ");
+    let _ = writeln!(out, "[Unit]");
+    let _ = writeln!(
+      out,
+      "Description=trainer {}",
+      escape_specifiers(&self.args.join(" "))
+    );
+    let _ = writeln!(out, "After=network.target");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[Service]");
+    let _ = writeln!(out, "Type=notify");
+    let _ = writeln!(out, "NotifyAccess=main");
+    let _ = writeln!(out, "WatchdogSec={}", WATCHDOG_SECS);
+    if self.scope == ServiceScope::System {
+      if let Some(user) = &self.run_as {
+        let _ = writeln!(out, "User={}", user);
+      }
+      if let Some(group) = &self.run_group {
+        let _ = writeln!(out, "Group={}", group);
+      }
+    }
+    if let Some(workdir) = &self.workdir {
+      // Taken verbatim apart from specifiers: no quotes, no variables.
+      let _ = writeln!(
+        out,
+        "WorkingDirectory={}",
+        escape_specifiers(&workdir.to_string_lossy())
+      );
+    }
+    let _ = writeln!(out, "ExecStart={}", exec);
+    let _ = writeln!(out, "Restart=on-failure");
+    let _ = writeln!(out, "RestartSec=2");
+    if self.hardening {
+      let _ = writeln!(out);
+      out.push_str(&self.hardening_lines());
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[Install]");
+    let _ = writeln!(out, "WantedBy={}", self.scope.wanted_by());
+    out
+  }
+
+  pub fn render_socket(&self) -> Option<String> {
+    let listen = self.socket.as_ref()?;
+    let mut out = String::new();
+    let _ = writeln!(out, "[Unit]");
+    let _ = writeln!(out, "Description=Socket for {}.service", self.name);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[Socket]");
+    let _ = writeln!(out, "ListenStream={}", listen);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[Install]");
+    let _ = writeln!(out, "WantedBy=sockets.target");
+    Some(out)
+  }
+
+  /// The unit files to write, socket first.
+  pub fn files(&self) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    if let Some(socket) = self.render_socket() {
+      files.push((unit_path(self.scope, &self.name, "socket")?, socket));
+    }
+    files.push((unit_path(self.scope, &self.name, "service")?, self.render()));
+    Ok(files)
+  }
+
+  /// Sandboxing the service doesn't need to give up. User managers can't set up mount
+  /// namespaces without privileges, so user units only get the seccomp-based options.
+  fn hardening_lines(&self) -> String {
+    let mut lines = vec![
+      "NoNewPrivileges=yes",
+      "LockPersonality=yes",
+      "RestrictRealtime=yes",
+      "RestrictSUIDSGID=yes",
+      "MemoryDenyWriteExecute=yes",
+      "SystemCallArchitectures=native",
+      "SystemCallFilter=@system-service",
+      "RestrictAddressFamilies=AF_INET AF_INET6 AF_UNIX",
+    ];
+    if self.scope == ServiceScope::System {
+      lines.extend([
+        "CapabilityBoundingSet=",
+        "PrivateTmp=yes",
+        "PrivateDevices=yes",
+        "ProtectSystem=strict",
+        "ProtectHome=read-only",
+        "ProtectKernelTunables=yes",
+        "ProtectKernelModules=yes",
+        "ProtectKernelLogs=yes",
+        "ProtectControlGroups=yes",
+        "ProtectClock=yes",
+        "ProtectHostname=yes",
+        "RestrictNamespaces=yes",
+        // History goes to /var/lib/trainer, since ProtectHome= makes the home read-only.
+        "StateDirectory=trainer",
+        "Environment=XDG_DATA_HOME=/var/lib",
+      ]);
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+  }
+
+  /// Writes the units, reloads systemd and starts the service (or its socket) now and on
+  /// boot (or login).
+  pub fn install(&self, dry_run: bool) -> Result<()> {
+    let files = self.files()?;
+    let unit = if self.socket.is_some() {
+      format!("{}.socket", self.name)
+    } else {
+      format!("{}.service", self.name)
+    };
+    let commands: [&[&str]; 2] = [&["daemon-reload"], &["enable", "--now", &unit]];
+    if dry_run {
+      for (path, content) in &files {
+        println!("# would write {}", path.display());
+        print!("{}", content);
+      }
+      for args in commands {
+        println!("# would run: {}", self.scope.systemctl_line(args));
+      }
+      return Ok(());
+    }
+    for (path, content) in &files {
+      if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+      }
+      fs::write(path, content).wrap_err_with(|| format!("writing {}", path.display()))?;
+      tracing::info!("wrote {}", path.display());
+    }
+    for args in commands {
+      systemctl(self.scope, args)?;
+    }
+    let user = if self.scope == ServiceScope::User {
+      "--user "
+    } else {
+      ""
+    };
+    for (path, _) in &files {
+      println!("Installed: {}", path.display());
+    }
+    println!("Status: systemctl {}status --no-pager {}", user, self.name);
+    println!("Logs: journalctl {}-u {} -f", user, self.name);
+    if self.scope == ServiceScope::User {
+      println!("To keep it running after logout: loginctl enable-linger");
+    } else if self.hardening {
+      println!("History: XDG_DATA_HOME=/var/lib trainer history");
+    }
+    Ok(())
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+pub fn unit_path(scope: ServiceScope, name: &str, kind: &str) -> Result<PathBuf> {
+  check_name(name)?;
+  let dir = match scope {
+    ServiceScope::System => PathBuf::from("/etc/systemd/system"),
+    ServiceScope::User => dirs::config_home()?.join("systemd").join("user"),
+  };
+  Ok(dir.join(format!("{}.{}", name, kind)))
+}
+
+/// Stops and disables the service and its socket, then removes their unit files.
+pub fn uninstall(scope: ServiceScope, name: &str, dry_run: bool) -> Result<()> {
+  let mut units = Vec::new();
+  for kind in ["socket", "service"] {
+    let path = unit_path(scope, name, kind)?;
+    if dry_run || path.exists() {
+      units.push((format!("{}.{}", name, kind), path));
+    }
+  }
+  if units.is_empty() {
+    return Err(eyre!("{} is not installed", name));
+  }
+  let mut disable = vec!["disable", "--now"];
+  disable.extend(units.iter().map(|(unit, _)| unit.as_str()));
+  if dry_run {
+    println!("# would run: {}", scope.systemctl_line(&disable));
+    for (_, path) in &units {
+      println!("# would remove {}", path.display());
+    }
+    println!("# would run: {}", scope.systemctl_line(&["daemon-reload"]));
+    return Ok(());
+  }
+  systemctl(scope, &disable)?;
+  for (_, path) in &units {
+    fs::remove_file(path).wrap_err_with(|| format!("removing {}", path.display()))?;
+    println!("Removed: {}", path.display());
+  }
+  systemctl(scope, &["daemon-reload"])?;
+  Ok(())
+}
+
+fn systemctl(scope: ServiceScope, args: &[&str]) -> Result<()> {
+  tracing::info!("{}", scope.systemctl_line(args));
+  let status = scope
+    .systemctl()
+    .args(args)
+    .status()
+    .wrap_err("running systemctl")?;
+  if !status.success() {
+    return Err(eyre!("{} failed: {}", scope.systemctl_line(args), status));
+  }
+  Ok(())
+}
+
+fn check_name(name: &str) -> Result<()> {
+  let valid = !name.is_empty()
+    && name
+      .chars()
+      .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | ':' | '@'));
+  if !valid {
+    return Err(eyre!("invalid service name {:?}", name));
+  }
+  Ok(())
+}
+
+/// `%` starts a specifier (`%h`, `%u`, …) in almost every unit setting.
+fn escape_specifiers(value: &str) -> String {
+  value.replace('%', "%%")
+}
+
+/// Quotes one word for `ExecStart=`; `%` and `$` would otherwise be expanded by systemd.
+fn quote(arg: &str) -> String {
+  let escaped = escape_specifiers(arg).replace('$', "$$");
+  let plain = !escaped.is_empty()
+    && escaped
+      .chars()
+      .all(|ch| ch.is_ascii_alphanumeric() || "-_./:=,+@%$".contains(ch));
+  if plain {
+    return escaped;
+  }
+  let mut out = String::from("\"");
+  for ch in escaped.chars() {
+    match ch {
+      '"' | '\\' => {
+        out.push('\\');
+        out.push(ch);
+      }
+      _ => out.push(ch),
+    }
+  }
+  out.push('"');
+  out
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  #[test]
+  fn quote_leaves_plain_words() {
+    assert_eq!(quote("squat-web"), "squat-web");
+    assert_eq!(quote("--addr=0.0.0.0:12002"), "--addr=0.0.0.0:12002");
+    assert_eq!(quote("/usr/local/bin/trainer"), "/usr/local/bin/trainer");
+  }
+
+  #[test]
+  fn quote_escapes_specifiers_and_variables() {
+    assert_eq!(quote("50%"), "50%%");
+    assert_eq!(quote("$HOME"), "$$HOME");
+    assert_eq!(quote("%h/${USER}"), "\"%%h/$${USER}\"");
+  }
+
+  #[test]
+  fn quote_wraps_spaces_quotes_and_backslashes() {
+    assert_eq!(quote("Slow Squat"), "\"Slow Squat\"");
+    assert_eq!(quote(r#"say "hi""#), r#""say \"hi\"""#);
+    assert_eq!(quote(r"C:\tmp"), r#""C:\\tmp""#);
+    assert_eq!(quote("it's"), "\"it's\"");
+    assert_eq!(quote(""), "\"\"");
+  }
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  fn unit(scope: ServiceScope) -> ServiceUnit {
+    ServiceUnit {
+      name: DEFAULT_SERVICE_NAME.to_string(),
+      scope,
+      bin: PathBuf::from("/usr/local/bin/trainer"),
+      args: [
+        "run",
+        "--web",
+        "--addr",
+        "0.0.0.0:12002",
+        "--plan",
+        "/srv/trainer/leg day 100%.toml",
+      ]
+      .map(String::from)
+      .to_vec(),
+      run_as: Some("trainer".to_string()),
+      run_group: Some("trainer".to_string()),
+      workdir: None,
+      hardening: true,
+      socket: None,
+    }
+  }
+
+  fn section<'a>(text: &'a str, name: &str) -> &'a str {
+    let start = text.find(name).unwrap();
+    let end = text[start..]
+      .find("\n\n[")
+      .map_or(text.len(), |end| start + end + 1);
+    &text[start..end]
+  }
+
+  #[test]
+  fn render_system_service_with_hardening() {
+    let text = unit(ServiceScope::System).render();
+    assert_eq!(
+      section(&text, "[Service]"),
+      "[Service]
+Type=notify
+NotifyAccess=main
+WatchdogSec=30
+User=trainer
+Group=trainer
+ExecStart=/usr/local/bin/trainer run --web --addr 0.0.0.0:12002 --plan \"/srv/trainer/leg day 100%%.toml\"
+Restart=on-failure
+RestartSec=2
+
+NoNewPrivileges=yes
+LockPersonality=yes
+RestrictRealtime=yes
+RestrictSUIDSGID=yes
+MemoryDenyWriteExecute=yes
+SystemCallArchitectures=native
+SystemCallFilter=@system-service
+RestrictAddressFamilies=AF_INET AF_INET6 AF_UNIX
+CapabilityBoundingSet=
+PrivateTmp=yes
+PrivateDevices=yes
+ProtectSystem=strict
+ProtectHome=read-only
+ProtectKernelTunables=yes
+ProtectKernelModules=yes
+ProtectKernelLogs=yes
+ProtectControlGroups=yes
+ProtectClock=yes
+ProtectHostname=yes
+RestrictNamespaces=yes
+StateDirectory=trainer
+Environment=XDG_DATA_HOME=/var/lib
+"
+    );
+    assert!(text.ends_with("[Install]\nWantedBy=multi-user.target\n"));
+  }
+
+  #[test]
+  fn render_user_service_keeps_seccomp_options_only() {
+    let text = unit(ServiceScope::User).render();
+    let service = section(&text, "[Service]");
+    assert!(service.contains("\nSystemCallFilter=@system-service\n"));
+    for key in [
+      "User=",
+      "Group=",
+      "ProtectSystem=",
+      "PrivateTmp=",
+      "StateDirectory=",
+    ] {
+      assert!(
+        !service.contains(&format!("\n{}", key)),
+        "{} in a user unit",
+        key
+      );
+    }
+    assert!(text.ends_with("[Install]\nWantedBy=default.target\n"));
+  }
+
+  #[test]
+  fn render_without_hardening() {
+    let mut unit = unit(ServiceScope::System);
+    unit.hardening = false;
+    let text = unit.render();
+    assert!(!text.contains("NoNewPrivileges="));
+    assert!(section(&text, "[Service]").ends_with("RestartSec=2\n"));
+  }
+
+  #[test]
+  fn render_unit_section_escapes_specifiers() {
+    let text = unit(ServiceScope::User).render();
+    assert!(text.starts_with(
+      "[Unit]\nDescription=trainer run --web --addr 0.0.0.0:12002 --plan /srv/trainer/leg day 100%%.toml\n"
+    ));
+  }
+
+  #[test]
+  fn render_working_directory_verbatim() {
+    let mut unit = unit(ServiceScope::System);
+    unit.workdir = Some(PathBuf::from("/srv/my plans/$USER/100%"));
+    let text = unit.render();
+    assert!(
+      text.contains("\nWorkingDirectory=/srv/my plans/$USER/100%%\nExecStart="),
+      "{}",
+      text
+    );
+  }
+}
@@ -0,0 +1,751 @@
+/* use  **************************************************************************************************/
+
+use crate::history::{self, SessionConfig, SessionRecord, SessionSource, SessionStatus};
+use crate::live::{LiveHub, LiveState, RemoteCommand};
+use crate::timeline::{Phase, WorkoutTimeline};
+use crate::web::EventStream;
+use chrono::{DateTime, FixedOffset, Local};
+use color_eyre::eyre::{Result, eyre};
+use serde::Serialize;
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, MutexGuard},
+  thread,
+  time::{Duration, Instant},
+};
+
+/* global const  *****************************************************************************************/
+
+/// Matches `countdownSeconds` in the web page.
+const COUNTDOWN: Duration = Duration::from_secs(5);
+/// Client sessions kept at once; idle ones make room for new clients.
+const MAX_CLIENTS: usize = 64;
+const MAX_CLIENT_ID_LEN: usize = 64;
+/// How often a stream waiting for its client's first request looks for the session, and
+/// how many looks between keep-alive pings.
+const PENDING_POLL: Duration = Duration::from_millis(250);
+const PENDING_PINGS: u32 = 60;
+
+/* struct  ***********************************************************************************************/
+
+/// The workout a session runs, resolved from the starting page's query string.
+pub struct SessionWorkout {
+  pub title: String,
+  pub query: String,
+  pub timeline: WorkoutTimeline,
+}
+
+/// Workout clock owned by the server. Times are milliseconds on the server's monotonic clock
+/// (see `SharedSession::now_ms`); pages translate them with their measured clock offset.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+  /// When the workout clock starts; in the future while the countdown runs.
+  pub started_at: Option<f64>,
+  pub paused_at: Option<f64>,
+  pub paused_ms: f64,
+  pub pauses: u32,
+  pub stopped: bool,
+  pub revision: u64,
+  /// Query string of the running workout, so every page loads the same one.
+  pub query: String,
+  pub total_ms: f64,
+}
+
+/// Where the session is right now, for `/api/session`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionProgress {
+  pub running: bool,
+  pub counting_down: bool,
+  pub paused: bool,
+  pub done: bool,
+  pub exercise: String,
+  pub phase: Phase,
+  pub set: u32,
+  pub sets: u32,
+  pub rep: u32,
+  pub reps: u32,
+  pub completed_reps: u32,
+  pub elapsed_ms: f64,
+  pub remaining_ms: f64,
+}
+
+struct Inner {
+  state: SessionState,
+  workout: Option<SessionWorkout>,
+  started_wall: DateTime<FixedOffset>,
+  recorded: bool,
+  /// `history::record`, except in tests.
+  recorder: fn(&SessionRecord),
+}
+
+/// A web session run by the server; pages only display it and ask for changes.
+pub struct SharedSession {
+  epoch: Instant,
+  inner: Mutex<Inner>,
+}
+
+/// A session and the hub its pages, viewers and remotes listen on.
+struct Channel {
+  session: SharedSession,
+  live: LiveHub,
+}
+
+struct Clients {
+  channels: HashMap<String, Arc<Channel>>,
+  /// Client whose session was controlled last.
+  latest: Option<String>,
+  /// Set on shutdown; ends streams still waiting for a session.
+  closed: bool,
+}
+
+/// Every session of a `squat-web` server: with `--group` one for everybody, otherwise one
+/// per client id, so people sharing a server never get pulled into each other's workout.
+/// Requests without an id (`/view`, `/remote`, curl) go to the session controlled last.
+pub struct Sessions {
+  epoch: Instant,
+  recorder: fn(&SessionRecord),
+  group: Option<Arc<Channel>>,
+  clients: Mutex<Clients>,
+  /// Mirrors the latest client's channel for streams that didn't name a session.
+  lobby: LiveHub,
+}
+
+/* impl  *************************************************************************************************/
+
+impl SharedSession {
+  /// `epoch` is shared by every session of a server, so one clock serves them all.
+  fn new(epoch: Instant, recorder: fn(&SessionRecord)) -> Self {
+    Self {
+      epoch,
+      inner: Mutex::new(Inner {
+        state: SessionState::default(),
+        workout: None,
+        started_wall: Local::now().fixed_offset(),
+        recorded: false,
+        recorder,
+      }),
+    }
+  }
+
+  pub fn now_ms(&self) -> f64 {
+    self.epoch.elapsed().as_secs_f64() * 1000.0
+  }
+
+  pub fn snapshot(&self) -> SessionState {
+    self.lock().state.clone()
+  }
+
+  pub fn progress(&self) -> Option<SessionProgress> {
+    let now = self.now_ms();
+    let inner = self.lock();
+    let workout = inner.workout.as_ref()?;
+    let elapsed = inner.elapsed_ms(now);
+    let at = workout
+      .timeline
+      .at(Duration::from_secs_f64(elapsed.max(0.0) / 1000.0));
+    Some(SessionProgress {
+      running: inner.is_running(now),
+      counting_down: elapsed < 0.0,
+      paused: inner.state.paused_at.is_some(),
+      done: at.done,
+      exercise: at.exercise,
+      phase: at.phase,
+      set: at.set,
+      sets: at.sets,
+      rep: at.rep,
+      reps: at.reps,
+      completed_reps: at.total_completed_reps,
+      elapsed_ms: elapsed,
+      remaining_ms: at.remaining.as_secs_f64() * 1000.0,
+    })
+  }
+
+  /// Applies `command` and returns the new state, or `None` when it changes nothing.
+  /// `workout` is only used when the command starts a new session.
+  pub fn apply(&self, command: RemoteCommand, workout: SessionWorkout) -> Option<SessionState> {
+    let now = self.now_ms();
+    let mut inner = self.lock();
+    let running = inner.is_running(now);
+    let counting_down = running && inner.elapsed_ms(now) < 0.0;
+    match command {
+      RemoteCommand::Start | RemoteCommand::Skip if !running => {
+        // A run that ran out since the last poll goes to the history before it's replaced.
+        inner.record_if_done(now);
+        let delay = if command == RemoteCommand::Start {
+          COUNTDOWN.as_secs_f64() * 1000.0
+        } else {
+          0.0
+        };
+        inner.state = SessionState {
+          started_at: Some(now + delay),
+          revision: inner.state.revision,
+          query: workout.query.clone(),
+          total_ms: workout.timeline.total().as_secs_f64() * 1000.0,
+          ..SessionState::default()
+        };
+        inner.workout = Some(workout);
+        inner.started_wall = Local::now().fixed_offset();
+        inner.recorded = false;
+      }
+      RemoteCommand::Skip if counting_down => inner.state.started_at = Some(now),
+      RemoteCommand::Pause if running && !counting_down && inner.state.paused_at.is_none() => {
+        inner.state.paused_at = Some(now);
+        inner.state.pauses += 1;
+      }
+      RemoteCommand::Resume if running && inner.state.paused_at.is_some() => {
+        let paused_at = inner.state.paused_at.take().unwrap_or(now);
+        inner.state.paused_ms += now - paused_at;
+      }
+      RemoteCommand::Stop if running => inner.finish(SessionStatus::Aborted, now),
+      _ => return None,
+    }
+    inner.state.revision += 1;
+    Some(inner.state.clone())
+  }
+
+  /// Records a session whose clock ran out; call periodically.
+  pub fn poll(&self) {
+    let now = self.now_ms();
+    self.lock().record_if_done(now);
+  }
+
+  /// Ends a running session because the server is going away and records it as
+  /// interrupted; returns the final state for the pages still connected.
+  pub fn interrupt(&self) -> Option<SessionState> {
+    let now = self.now_ms();
+    let mut inner = self.lock();
+    if !inner.is_running(now) {
+      return None;
+    }
+    inner.finish(SessionStatus::Interrupted, now);
+    inner.state.revision += 1;
+    Some(inner.state.clone())
+  }
+
+  fn is_running(&self) -> bool {
+    let now = self.now_ms();
+    self.lock().is_running(now)
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Inner> {
+    self.inner.lock().unwrap_or_else(|err| err.into_inner())
+  }
+}
+
+impl Channel {
+  fn new(epoch: Instant, recorder: fn(&SessionRecord)) -> Arc<Self> {
+    let channel = Self {
+      session: SharedSession::new(epoch, recorder),
+      live: LiveHub::default(),
+    };
+    channel
+      .live
+      .publish_latest("session", &channel.session.snapshot());
+    Arc::new(channel)
+  }
+}
+
+impl Sessions {
+  pub fn new(group: bool) -> Self {
+    Self::with_recorder(group, history::record)
+  }
+
+  fn with_recorder(group: bool, recorder: fn(&SessionRecord)) -> Self {
+    let epoch = Instant::now();
+    Self {
+      epoch,
+      recorder,
+      group: group.then(|| Channel::new(epoch, recorder)),
+      clients: Mutex::new(Clients {
+        channels: HashMap::new(),
+        latest: None,
+        closed: false,
+      }),
+      lobby: LiveHub::default(),
+    }
+  }
+
+  /// Server clock shared by all sessions, in the milliseconds session states use.
+  pub fn now_ms(&self) -> f64 {
+    self.epoch.elapsed().as_secs_f64() * 1000.0
+  }
+
+  /// Applies `command` to the client's session and tells its pages. The client it went to
+  /// becomes the one that id-less requests follow.
+  pub fn apply(
+    &self,
+    id: Option<&str>,
+    command: RemoteCommand,
+    workout: SessionWorkout,
+  ) -> Result<()> {
+    let (key, channel) = self.resolve(id)?;
+    if let Some(key) = &key {
+      let mut clients = self.lock();
+      if clients.latest.as_ref() != Some(key) {
+        clients.latest = Some(key.clone());
+        channel.live.copy_latest_to(&self.lobby);
+      }
+    }
+    if let Some(state) = channel.session.apply(command, workout) {
+      self.publish(key.as_deref(), &channel.live, "session", &state);
+    }
+    Ok(())
+  }
+
+  /// Passes a workout page's live state on to the viewers of its session.
+  pub fn publish_state(&self, id: Option<&str>, state: &LiveState) -> Result<()> {
+    let (key, channel) = self.resolve(id)?;
+    self.publish(key.as_deref(), &channel.live, "state", state);
+    Ok(())
+  }
+
+  /// `/api/session`: the session state and where its workout is. Clients that haven't sent
+  /// anything yet get the idle state their session will start from.
+  pub fn snapshot(&self, id: Option<&str>) -> Result<(SessionState, Option<SessionProgress>)> {
+    Ok(match self.lookup(id)? {
+      Some((_, channel)) => (channel.session.snapshot(), channel.session.progress()),
+      None => (SessionState::default(), None),
+    })
+  }
+
+  /// Streams the session's events; without an id, whichever session was controlled last.
+  /// A client without a session yet gets the idle state, then its session's events once a
+  /// command or live state has created it.
+  pub fn serve(&self, id: Option<&str>, mut stream: EventStream) -> Result<()> {
+    if self.group.is_none() && id.is_none() {
+      self.lobby.serve(stream);
+      return Ok(());
+    }
+    let mut waited = 0;
+    loop {
+      if let Some((_, channel)) = self.lookup(id)? {
+        channel.live.serve(stream);
+        return Ok(());
+      }
+      if waited == 0 {
+        stream.send("session", &serde_json::to_string(&SessionState::default())?)?;
+      } else if waited % PENDING_PINGS == 0 {
+        stream.ping()?;
+      }
+      if self.lock().closed {
+        return Ok(());
+      }
+      waited += 1;
+      thread::sleep(PENDING_POLL);
+    }
+  }
+
+  /// Records sessions whose clock ran out; call periodically.
+  pub fn poll(&self) {
+    for channel in self.channels() {
+      channel.session.poll();
+    }
+  }
+
+  /// Interrupts every running session, tells its pages, and ends all event streams.
+  pub fn shut_down(&self) {
+    let latest = {
+      let mut clients = self.lock();
+      clients.closed = true;
+      clients.latest.clone()
+    };
+    for (key, channel) in self.keyed_channels() {
+      if let Some(state) = channel.session.interrupt() {
+        channel.live.publish_latest("session", &state);
+        if key.is_some() && key == latest {
+          self.lobby.publish_latest("session", &state);
+        }
+      }
+      channel.live.close();
+    }
+    self.lobby.close();
+  }
+
+  /// The client key (`None` in group mode) and channel a request reads, if there is one.
+  fn lookup(&self, id: Option<&str>) -> Result<Option<(Option<String>, Arc<Channel>)>> {
+    if let Some(group) = &self.group {
+      return Ok(Some((None, group.clone())));
+    }
+    let clients = self.lock();
+    let key = match id {
+      Some(id) => {
+        check_client_id(id)?;
+        id.to_string()
+      }
+      None => match &clients.latest {
+        Some(key) => key.clone(),
+        None => return Ok(None),
+      },
+    };
+    Ok(
+      clients
+        .channels
+        .get(&key)
+        .map(|channel| (Some(key.clone()), channel.clone())),
+    )
+  }
+
+  /// Like `lookup`, but creates the client's session; only for requests that change it.
+  fn resolve(&self, id: Option<&str>) -> Result<(Option<String>, Arc<Channel>)> {
+    if let Some(found) = self.lookup(id)? {
+      return Ok(found);
+    }
+    let Some(id) = id else {
+      return Err(eyre!(
+        "no session has been started yet; open the link from the workout page"
+      ));
+    };
+    let mut clients = self.lock();
+    if let Some(channel) = clients.channels.get(id) {
+      return Ok((Some(id.to_string()), channel.clone()));
+    }
+    if clients.channels.len() >= MAX_CLIENTS {
+      // Only sessions nobody is connected to or running.
+      clients
+        .channels
+        .retain(|_, channel| Arc::strong_count(channel) > 1 || channel.session.is_running());
+      let Clients {
+        channels, latest, ..
+      } = &mut *clients;
+      if latest
+        .as_ref()
+        .is_some_and(|key| !channels.contains_key(key))
+      {
+        *latest = None;
+      }
+      if clients.channels.len() >= MAX_CLIENTS {
+        return Err(eyre!("too many sessions"));
+      }
+    }
+    let channel = Channel::new(self.epoch, self.recorder);
+    clients.channels.insert(id.to_string(), channel.clone());
+    Ok((Some(id.to_string()), channel))
+  }
+
+  fn publish<T: Serialize>(
+    &self,
+    key: Option<&str>,
+    live: &LiveHub,
+    event: &'static str,
+    value: &T,
+  ) {
+    live.publish_latest(event, value);
+    if key.is_some() && self.lock().latest.as_deref() == key {
+      self.lobby.publish_latest(event, value);
+    }
+  }
+
+  fn channels(&self) -> Vec<Arc<Channel>> {
+    self
+      .keyed_channels()
+      .into_iter()
+      .map(|(_, channel)| channel)
+      .collect()
+  }
+
+  fn keyed_channels(&self) -> Vec<(Option<String>, Arc<Channel>)> {
+    match &self.group {
+      Some(group) => vec![(None, group.clone())],
+      None => self
+        .lock()
+        .channels
+        .iter()
+        .map(|(key, channel)| (Some(key.clone()), channel.clone()))
+        .collect(),
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Clients> {
+    self.clients.lock().unwrap_or_else(|err| err.into_inner())
+  }
+}
+
+impl Inner {
+  /// Workout clock time; negative during the countdown.
+  fn elapsed_ms(&self, now: f64) -> f64 {
+    let Some(started_at) = self.state.started_at else {
+      return 0.0;
+    };
+    let end = self.state.paused_at.unwrap_or(now);
+    end - started_at - self.state.paused_ms
+  }
+
+  /// Started, not stopped, and the workout clock hasn't run past the end.
+  fn is_running(&self, now: f64) -> bool {
+    self.state.started_at.is_some()
+      && !self.state.stopped
+      && self.elapsed_ms(now) < self.state.total_ms
+  }
+
+  /// Records the run as completed once its clock has run out.
+  fn record_if_done(&mut self, now: f64) {
+    if self.state.started_at.is_some() && !self.state.stopped && !self.is_running(now) {
+      self.record(SessionStatus::Completed, now);
+    }
+  }
+
+  /// Stops the clock before it ran out and records how far the session got.
+  fn finish(&mut self, status: SessionStatus, now: f64) {
+    if let Some(paused_at) = self.state.paused_at.take() {
+      self.state.paused_ms += now - paused_at;
+    }
+    self.state.stopped = true;
+    self.record(status, now);
+  }
+
+  fn record(&mut self, status: SessionStatus, now: f64) {
+    let Some(workout) = &self.workout else {
+      return;
+    };
+    if self.recorded {
+      return;
+    }
+    self.recorded = true;
+    let elapsed = Duration::from_secs_f64(self.elapsed_ms(now).max(0.0) / 1000.0);
+    let progress = workout.timeline.at(elapsed);
+    (self.recorder)(&SessionRecord {
+      started_at: self.started_wall,
+      ended_at: Local::now().fixed_offset(),
+      source: SessionSource::Web,
+      exercise: workout.title.clone(),
+      status,
+      reps_done: progress.total_completed_reps,
+      reps_total: workout.timeline.total_reps(),
+      sets_done: progress.completed_sets,
+      sets_total: workout.timeline.sets().len() as u32,
+      pauses: self.state.pauses,
+      paused_secs: self.state.paused_ms / 1000.0,
+      active_secs: progress.active_elapsed.as_secs_f64(),
+      config: SessionConfig::from_timeline(&workout.timeline),
+    });
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// Ids come from the page's local storage: 1–64 ASCII letters, digits, `-` or `_`.
+fn check_client_id(id: &str) -> Result<()> {
+  let valid = !id.is_empty()
+    && id.len() <= MAX_CLIENT_ID_LEN
+    && id
+      .bytes()
+      .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_');
+  if !valid {
+    return Err(eyre!("invalid session id {:?}", id));
+  }
+  Ok(())
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+  use crate::timeline::Tempo;
+  use std::{
+    cell::RefCell,
+    io::{self, Write},
+  };
+
+  /// What a stream wrote, readable from another thread.
+  #[derive(Clone, Default)]
+  struct Sink(Arc<Mutex<Vec<u8>>>);
+
+  impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.0.lock().unwrap().extend_from_slice(buf);
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  impl Sink {
+    fn stream(&self) -> EventStream {
+      EventStream::from_writer(Box::new(self.clone()))
+    }
+
+    fn text(&self) -> String {
+      String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+  }
+
+  thread_local! {
+    /// Sessions recorded on this test's thread.
+    static RECORDED: RefCell<Vec<SessionRecord>> = const { RefCell::new(Vec::new()) };
+  }
+
+  fn remember(record: &SessionRecord) {
+    RECORDED.with(|recorded| recorded.borrow_mut().push(record.clone()));
+  }
+
+  fn recorded() -> Vec<(SessionStatus, u32)> {
+    RECORDED.with(|recorded| {
+      recorded
+        .borrow()
+        .iter()
+        .map(|record| (record.status, record.reps_done))
+        .collect()
+    })
+  }
+
+  /// One rep of 3 ms, over before the next line of the test runs.
+  fn short_workout() -> SessionWorkout {
+    let tempo = Tempo {
+      down: 0.001,
+      hold: 0.001,
+      up: 0.001,
+    };
+    SessionWorkout {
+      title: "Slow Squat".to_string(),
+      query: String::new(),
+      timeline: WorkoutTimeline::uniform(tempo, 1, 1, Duration::ZERO),
+    }
+  }
+
+  #[test]
+  fn start_records_a_finished_run_before_replacing_it() {
+    let sessions = Sessions::with_recorder(false, remember);
+    let alice = Some("alice");
+    sessions
+      .apply(alice, RemoteCommand::Skip, short_workout())
+      .unwrap();
+    thread::sleep(Duration::from_millis(20));
+    // No poll in between: the new start has to record the old run itself.
+    sessions
+      .apply(alice, RemoteCommand::Start, short_workout())
+      .unwrap();
+    assert_eq!(recorded(), [(SessionStatus::Completed, 1)]);
+
+    // The countdown of the new run isn't over, so polling records nothing more.
+    sessions.poll();
+    assert_eq!(recorded().len(), 1);
+  }
+
+  #[test]
+  fn poll_records_each_run_once() {
+    let sessions = Sessions::with_recorder(true, remember);
+    sessions
+      .apply(None, RemoteCommand::Skip, short_workout())
+      .unwrap();
+    thread::sleep(Duration::from_millis(20));
+    sessions.poll();
+    sessions.poll();
+    sessions
+      .apply(None, RemoteCommand::Skip, short_workout())
+      .unwrap();
+    assert_eq!(recorded(), [(SessionStatus::Completed, 1)]);
+  }
+
+  #[test]
+  fn reads_do_not_create_sessions() {
+    let sessions = Sessions::with_recorder(false, remember);
+    for idx in 0..MAX_CLIENTS + 8 {
+      let id = format!("reader-{}", idx);
+      let (state, progress) = sessions.snapshot(Some(&id)).unwrap();
+      assert_eq!((state.started_at, state.revision), (None, 0));
+      assert!(progress.is_none());
+    }
+    assert!(sessions.lock().channels.is_empty());
+
+    sessions
+      .apply(Some("writer"), RemoteCommand::Pause, short_workout())
+      .unwrap();
+    assert_eq!(sessions.lock().channels.len(), 1);
+  }
+
+  #[test]
+  fn serve_waits_for_the_client_session() {
+    let sessions = Sessions::with_recorder(false, remember);
+    let sink = Sink::default();
+    thread::scope(|scope| {
+      let viewer = scope.spawn(|| sessions.serve(Some("viewer"), sink.stream()));
+      thread::sleep(PENDING_POLL * 2);
+      assert!(sessions.lock().channels.is_empty());
+      assert!(sink.text().contains("\"startedAt\":null"));
+
+      sessions
+        .apply(Some("viewer"), RemoteCommand::Start, short_workout())
+        .unwrap();
+      thread::sleep(PENDING_POLL * 2);
+      sessions.shut_down();
+      viewer.join().unwrap().unwrap();
+    });
+    let text = sink.text();
+    assert!(text.contains("\"revision\":1"), "{}", text);
+    assert!(text.contains("\"stopped\":true"), "{}", text);
+  }
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+  use crate::timeline::Tempo;
+
+  fn workout(query: &str) -> SessionWorkout {
+    let tempo = Tempo {
+      down: 1.0,
+      hold: 1.0,
+      up: 1.0,
+    };
+    SessionWorkout {
+      title: "Slow Squat".to_string(),
+      query: query.to_string(),
+      timeline: WorkoutTimeline::uniform(tempo, 2, 1, Duration::ZERO),
+    }
+  }
+
+  fn started(sessions: &Sessions, id: Option<&str>) -> Option<String> {
+    let (state, _) = sessions.snapshot(id).unwrap();
+    state.started_at.map(|_| state.query)
+  }
+
+  #[test]
+  fn clients_get_their_own_session() {
+    let sessions = Sessions::new(false);
+    assert!(
+      sessions
+        .apply(None, RemoteCommand::Start, workout(""))
+        .is_err()
+    );
+    sessions
+      .apply(Some("alice"), RemoteCommand::Start, workout("count=2"))
+      .unwrap();
+    assert_eq!(
+      started(&sessions, Some("alice")),
+      Some("count=2".to_string())
+    );
+    assert_eq!(started(&sessions, Some("bob")), None);
+    // Requests without an id follow the session controlled last.
+    assert_eq!(started(&sessions, None), Some("count=2".to_string()));
+    sessions
+      .apply(Some("bob"), RemoteCommand::Start, workout(""))
+      .unwrap();
+    assert_eq!(started(&sessions, None), Some(String::new()));
+  }
+
+  #[test]
+  fn group_mode_shares_one_session() {
+    let sessions = Sessions::new(true);
+    sessions
+      .apply(Some("alice"), RemoteCommand::Start, workout(""))
+      .unwrap();
+    assert!(started(&sessions, Some("bob")).is_some());
+    assert!(started(&sessions, None).is_some());
+  }
+
+  #[test]
+  fn client_ids_are_checked() {
+    let sessions = Sessions::new(false);
+    for id in ["", "a b", "../x", &"x".repeat(MAX_CLIENT_ID_LEN + 1)] {
+      assert!(sessions.snapshot(Some(id)).is_err(), "{:?} accepted", id);
+    }
+    assert!(sessions.snapshot(Some("0b1e-4c_9F")).is_ok());
+  }
+}
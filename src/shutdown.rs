@@ -0,0 +1,60 @@
+/* use  **************************************************************************************************/
+
+use color_eyre::eyre::Result;
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use std::sync::{
+  Arc,
+  atomic::{AtomicUsize, Ordering},
+};
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownSignal {
+  /// Ctrl+C outside raw mode; the user chose to stop.
+  Interrupt,
+  /// `systemctl stop`, `kill` and friends.
+  Terminate,
+  /// The controlling terminal went away, e.g. a dropped SSH session.
+  Hangup,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// Remembers the last SIGINT, SIGTERM or SIGHUP instead of dying on it, so loops can wind
+/// down, restore the terminal and record the session.
+#[derive(Clone)]
+pub struct Shutdown {
+  signal: Arc<AtomicUsize>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl ShutdownSignal {
+  pub fn name(self) -> &'static str {
+    match self {
+      ShutdownSignal::Interrupt => "SIGINT",
+      ShutdownSignal::Terminate => "SIGTERM",
+      ShutdownSignal::Hangup => "SIGHUP",
+    }
+  }
+}
+
+impl Shutdown {
+  pub fn install() -> Result<Self> {
+    let signal = Arc::new(AtomicUsize::new(0));
+    for sig in [SIGINT, SIGTERM, SIGHUP] {
+      signal_hook::flag::register_usize(sig, signal.clone(), sig as usize)?;
+    }
+    Ok(Self { signal })
+  }
+
+  pub fn requested(&self) -> Option<ShutdownSignal> {
+    match self.signal.load(Ordering::SeqCst) as i32 {
+      SIGINT => Some(ShutdownSignal::Interrupt),
+      SIGTERM => Some(ShutdownSignal::Terminate),
+      SIGHUP => Some(ShutdownSignal::Hangup),
+      _ => None,
+    }
+  }
+}
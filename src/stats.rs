@@ -0,0 +1,315 @@
+/* use  **************************************************************************************************/
+
+use crate::history::{SessionRecord, SessionStatus, format_hms};
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use serde::Serialize;
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  fmt::Write as _,
+};
+
+/* struct  ***********************************************************************************************/
+
+#[derive(Debug, Serialize)]
+pub struct WeeklyVolume {
+  /// ISO week, e.g. `2026-W42`.
+  pub week: String,
+  pub sessions: usize,
+  pub reps: u32,
+  pub time_under_tension_secs: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+  pub sessions: usize,
+  pub completed: usize,
+  pub completion_rate: f64,
+  pub total_reps: u32,
+  pub time_under_tension_secs: f64,
+  pub current_streak_days: u32,
+  pub longest_streak_days: u32,
+  pub weekly: Vec<WeeklyVolume>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl Stats {
+  /// Streaks count days with at least one rep; the current streak stays alive until a full day is missed.
+  /// `weekly` has a row for each of the `weeks` ISO weeks up to the one with `today`, empty ones included.
+  pub fn compute(records: &[SessionRecord], today: NaiveDate, weeks: usize) -> Self {
+    let completed = records
+      .iter()
+      .filter(|record| record.status == SessionStatus::Completed)
+      .count();
+
+    let this_week = week_start(today);
+    let mut weekly: BTreeMap<NaiveDate, WeeklyVolume> = (0..weeks as i64)
+      .rev()
+      .map(|ago| this_week - ChronoDuration::weeks(ago))
+      .map(|monday| {
+        let week = monday.iso_week();
+        let volume = WeeklyVolume {
+          week: format!("{}-W{:02}", week.year(), week.week()),
+          sessions: 0,
+          reps: 0,
+          time_under_tension_secs: 0.0,
+        };
+        (monday, volume)
+      })
+      .collect();
+    for record in records {
+      if let Some(entry) = weekly.get_mut(&week_start(record.started_at.date_naive())) {
+        entry.sessions += 1;
+        entry.reps += record.reps_done;
+        entry.time_under_tension_secs += record.active_secs;
+      }
+    }
+    let weekly = weekly.into_values().collect();
+
+    let days: BTreeSet<NaiveDate> = records
+      .iter()
+      .filter(|record| record.reps_done > 0)
+      .map(|record| record.started_at.date_naive())
+      .collect();
+
+    Self {
+      sessions: records.len(),
+      completed,
+      completion_rate: if records.is_empty() {
+        0.0
+      } else {
+        completed as f64 / records.len() as f64
+      },
+      total_reps: records.iter().map(|record| record.reps_done).sum(),
+      time_under_tension_secs: records.iter().map(|record| record.active_secs).sum(),
+      current_streak_days: current_streak(&days, today),
+      longest_streak_days: longest_streak(&days),
+      weekly,
+    }
+  }
+
+  pub fn format_table(&self) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "Sessions:            {}", self.sessions);
+    let _ = writeln!(
+      output,
+      "Completion rate:     {:.1}% ({}/{})",
+      self.completion_rate * 100.0,
+      self.completed,
+      self.sessions
+    );
+    let _ = writeln!(output, "Total reps:          {}", self.total_reps);
+    let _ = writeln!(
+      output,
+      "Time under tension:  {}",
+      format_hms(self.time_under_tension_secs)
+    );
+    let _ = writeln!(
+      output,
+      "Current streak:      {} days",
+      self.current_streak_days
+    );
+    let _ = writeln!(
+      output,
+      "Longest streak:      {} days",
+      self.longest_streak_days
+    );
+    let _ = writeln!(output);
+    let _ = writeln!(
+      output,
+      "{:<8}  {:>8}  {:>6}  {:>8}",
+      "WEEK", "SESSIONS", "REPS", "TUT"
+    );
+    for week in &self.weekly {
+      let _ = writeln!(
+        output,
+        "{:<8}  {:>8}  {:>6}  {:>8}",
+        week.week,
+        week.sessions,
+        week.reps,
+        format_hms(week.time_under_tension_secs)
+      );
+    }
+    output
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// Monday of the ISO week `day` is in.
+fn week_start(day: NaiveDate) -> NaiveDate {
+  day - ChronoDuration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+fn current_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> u32 {
+  let mut day = if days.contains(&today) {
+    today
+  } else {
+    today - ChronoDuration::days(1)
+  };
+  let mut streak = 0;
+  while days.contains(&day) {
+    streak += 1;
+    day -= ChronoDuration::days(1);
+  }
+  streak
+}
+
+fn longest_streak(days: &BTreeSet<NaiveDate>) -> u32 {
+  let mut longest = 0;
+  let mut run = 0;
+  let mut prev: Option<NaiveDate> = None;
+  for &day in days {
+    run = match prev {
+      Some(prev) if day - prev == ChronoDuration::days(1) => run + 1,
+      _ => 1,
+    };
+    longest = longest.max(run);
+    prev = Some(day);
+  }
+  longest
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  fn date(text: &str) -> NaiveDate {
+    text.parse().unwrap()
+  }
+
+  fn days(dates: &[&str]) -> BTreeSet<NaiveDate> {
+    dates.iter().map(|text| date(text)).collect()
+  }
+
+  #[test]
+  fn current_streak_counts_back_from_today_or_yesterday() {
+    let trained = days(&["2026-10-14", "2026-10-15", "2026-10-16"]);
+    assert_eq!(current_streak(&trained, date("2026-10-16")), 3);
+    // Today isn't over yet, so yesterday's streak still counts.
+    assert_eq!(current_streak(&trained, date("2026-10-17")), 3);
+    assert_eq!(current_streak(&trained, date("2026-10-18")), 0);
+    assert_eq!(current_streak(&BTreeSet::new(), date("2026-10-18")), 0);
+  }
+
+  #[test]
+  fn current_streak_stops_at_a_gap() {
+    let trained = days(&["2026-10-10", "2026-10-11", "2026-10-13", "2026-10-14"]);
+    assert_eq!(current_streak(&trained, date("2026-10-14")), 2);
+  }
+
+  #[test]
+  fn longest_streak_finds_the_longest_run() {
+    let trained = days(&[
+      "2026-09-01",
+      "2026-09-02",
+      "2026-09-03",
+      "2026-09-05",
+      "2026-10-01",
+      "2026-10-02",
+    ]);
+    assert_eq!(longest_streak(&trained), 3);
+    assert_eq!(longest_streak(&days(&["2026-10-01"])), 1);
+    assert_eq!(longest_streak(&BTreeSet::new()), 0);
+  }
+
+  #[test]
+  fn week_start_is_monday() {
+    assert_eq!(week_start(date("2026-10-18")), date("2026-10-12"));
+    assert_eq!(week_start(date("2026-10-12")), date("2026-10-12"));
+  }
+}
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+  use crate::history::{SessionConfig, SessionSource};
+
+  fn record(started_at: &str, reps_done: u32, status: SessionStatus) -> SessionRecord {
+    let started_at = chrono::DateTime::parse_from_rfc3339(started_at).unwrap();
+    SessionRecord {
+      started_at,
+      ended_at: started_at,
+      source: SessionSource::Terminal,
+      exercise: "Slow Squat".to_string(),
+      status,
+      reps_done,
+      reps_total: 10,
+      sets_done: 1,
+      sets_total: 1,
+      pauses: 0,
+      paused_secs: 0.0,
+      active_secs: reps_done as f64 * 10.0,
+      config: SessionConfig {
+        total_secs: 100.0,
+        sets: Vec::new(),
+      },
+    }
+  }
+
+  #[test]
+  fn compute_counts_several_sessions_on_one_day_once() {
+    let records = [
+      record("2026-10-17T08:00:00+09:00", 10, SessionStatus::Completed),
+      record("2026-10-17T20:00:00+09:00", 4, SessionStatus::Aborted),
+      record("2026-10-18T07:00:00+09:00", 10, SessionStatus::Completed),
+      // No reps, so it doesn't extend the streak.
+      record("2026-10-16T07:00:00+09:00", 0, SessionStatus::Aborted),
+    ];
+    let stats = Stats::compute(&records, "2026-10-18".parse().unwrap(), 1);
+    assert_eq!(
+      (stats.sessions, stats.completed, stats.total_reps),
+      (4, 2, 24)
+    );
+    assert_eq!(
+      (stats.current_streak_days, stats.longest_streak_days),
+      (2, 2)
+    );
+    assert_eq!(stats.completion_rate, 0.5);
+  }
+
+  #[test]
+  fn compute_has_a_row_for_every_week_in_the_window() {
+    let records = [
+      record("2026-09-01T08:00:00+09:00", 10, SessionStatus::Completed),
+      record("2026-09-22T08:00:00+09:00", 10, SessionStatus::Completed),
+      record("2026-10-13T08:00:00+09:00", 5, SessionStatus::Aborted),
+      record("2026-10-18T08:00:00+09:00", 10, SessionStatus::Completed),
+    ];
+    let stats = Stats::compute(&records, "2026-10-18".parse().unwrap(), 4);
+    let weeks: Vec<(&str, usize, u32)> = stats
+      .weekly
+      .iter()
+      .map(|week| (week.week.as_str(), week.sessions, week.reps))
+      .collect();
+    assert_eq!(
+      weeks,
+      [
+        ("2026-W39", 1, 10),
+        ("2026-W40", 0, 0),
+        ("2026-W41", 0, 0),
+        ("2026-W42", 2, 15)
+      ]
+    );
+    assert_eq!(stats.weekly[3].time_under_tension_secs, 150.0);
+    // Totals still cover everything, outside the window too.
+    assert_eq!(stats.total_reps, 35);
+  }
+
+  #[test]
+  fn compute_weekly_crosses_the_year() {
+    let records = [record(
+      "2026-12-29T08:00:00+09:00",
+      10,
+      SessionStatus::Completed,
+    )];
+    let stats = Stats::compute(&records, "2027-01-05".parse().unwrap(), 3);
+    let weeks: Vec<&str> = stats.weekly.iter().map(|week| week.week.as_str()).collect();
+    assert_eq!(weeks, ["2026-W52", "2026-W53", "2027-W01"]);
+    assert_eq!(stats.weekly[1].sessions, 1);
+  }
+}
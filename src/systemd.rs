@@ -0,0 +1,263 @@
+/* use  **************************************************************************************************/
+
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use std::{
+  env,
+  net::TcpListener,
+  os::unix::{io::FromRawFd, net::UnixDatagram},
+  time::{Duration, Instant},
+};
+
+/* global const  *****************************************************************************************/
+
+/// First descriptor systemd passes (`SD_LISTEN_FDS_START`).
+const LISTEN_FDS_START: i32 = 3;
+
+/* struct  ***********************************************************************************************/
+
+/// Sends `sd_notify` messages when systemd gave us `NOTIFY_SOCKET`; a no-op otherwise.
+pub struct Notifier {
+  socket: Option<(UnixDatagram, String)>,
+  watchdog: Option<Duration>,
+  last_ping: Instant,
+}
+
+/* impl  *************************************************************************************************/
+
+impl Notifier {
+  pub fn from_env() -> Self {
+    let var = |key| env::var(key).ok();
+    Self::new(
+      var("NOTIFY_SOCKET").as_deref(),
+      var("WATCHDOG_USEC").as_deref(),
+      var("WATCHDOG_PID").as_deref(),
+    )
+  }
+
+  /// From the values of `NOTIFY_SOCKET`, `WATCHDOG_USEC` and `WATCHDOG_PID`.
+  pub fn new(
+    notify_socket: Option<&str>,
+    watchdog_usec: Option<&str>,
+    watchdog_pid: Option<&str>,
+  ) -> Self {
+    let socket = notify_socket
+      .filter(|path| !path.is_empty())
+      .and_then(|path| match UnixDatagram::unbound() {
+        Ok(socket) => Some((socket, path.to_string())),
+        Err(err) => {
+          tracing::warn!("cannot open notify socket: {}", err);
+          None
+        }
+      });
+    let watchdog = watchdog_usec
+      .filter(|_| watchdog_pid.is_none_or(for_this_process))
+      .and_then(|usec| usec.parse::<u64>().ok())
+      .filter(|usec| *usec > 0)
+      .map(Duration::from_micros);
+    Self {
+      socket,
+      watchdog,
+      last_ping: Instant::now(),
+    }
+  }
+
+  pub fn ready(&self, status: &str) {
+    self.notify(&format!("READY=1\nSTATUS={}", status));
+  }
+
+  pub fn stopping(&self) {
+    self.notify("STOPPING=1");
+  }
+
+  /// Pings the watchdog at half its interval; call from the loop that must stay alive.
+  pub fn watchdog(&mut self) {
+    let Some(interval) = self.watchdog else {
+      return;
+    };
+    if self.last_ping.elapsed() >= interval / 2 {
+      self.last_ping = Instant::now();
+      self.notify("WATCHDOG=1");
+    }
+  }
+
+  fn notify(&self, message: &str) {
+    let Some((socket, path)) = &self.socket else {
+      return;
+    };
+    if let Err(err) = send_to(socket, path, message) {
+      tracing::warn!("sd_notify to {} failed: {}", path, err);
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+/// The listening socket systemd passed through socket activation, if any. Clears the
+/// `LISTEN_*` variables so child processes don't try to take it too.
+pub fn listen_socket() -> Result<Option<TcpListener>> {
+  let pid = env::var("LISTEN_PID").ok();
+  if !pid.as_deref().is_some_and(for_this_process) {
+    return Ok(None);
+  }
+  let fds = env::var("LISTEN_FDS").ok();
+  unsafe {
+    env::remove_var("LISTEN_PID");
+    env::remove_var("LISTEN_FDS");
+    env::remove_var("LISTEN_FDNAMES");
+  }
+  listen_socket_from(pid.as_deref(), fds.as_deref())
+}
+
+/// From the values of `LISTEN_PID` and `LISTEN_FDS`; takes over descriptor 3 when they
+/// name this process.
+pub fn listen_socket_from(pid: Option<&str>, fds: Option<&str>) -> Result<Option<TcpListener>> {
+  let (Some(pid), Some(count)) = (pid, fds) else {
+    return Ok(None);
+  };
+  if !for_this_process(pid) {
+    return Ok(None);
+  }
+  let count: i32 = count
+    .parse()
+    .map_err(|_| eyre!("invalid LISTEN_FDS {:?}", count))?;
+  if count < 1 {
+    return Ok(None);
+  }
+  if count > 1 {
+    tracing::warn!("systemd passed {} sockets; using the first", count);
+  }
+  // Safety: systemd hands over descriptors starting at 3 and nothing else owns them.
+  let inherited = unsafe { TcpListener::from_raw_fd(LISTEN_FDS_START) };
+  // `try_clone` dups with close-on-exec, which inherited descriptors lack.
+  let listener = inherited
+    .try_clone()
+    .wrap_err("duplicating the socket from systemd")?;
+  drop(inherited);
+  listener
+    .local_addr()
+    .wrap_err("the socket from systemd is not a TCP listener")?;
+  Ok(Some(listener))
+}
+
+/// `LISTEN_PID` / `WATCHDOG_PID` target one process.
+fn for_this_process(pid: &str) -> bool {
+  pid.parse::<u32>().ok() == Some(std::process::id())
+}
+
+fn send_to(socket: &UnixDatagram, path: &str, message: &str) -> std::io::Result<usize> {
+  // A leading `@` names a socket in the abstract namespace.
+  if let Some(name) = path.strip_prefix('@') {
+    #[cfg(target_os = "linux")]
+    {
+      use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+      let addr = SocketAddr::from_abstract_name(name)?;
+      return socket.send_to_addr(message.as_bytes(), &addr);
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+      let _ = name;
+      return Err(std::io::Error::from(std::io::ErrorKind::Unsupported));
+    }
+  }
+  socket.send_to(message.as_bytes(), path)
+}
+
+/* test for pri ******************************************************************************************/
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+  use std::fs;
+
+  fn receive(socket: &UnixDatagram) -> String {
+    let mut buf = [0; 256];
+    let len = socket.recv(&mut buf).unwrap();
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+  }
+
+  fn own_pid() -> String {
+    std::process::id().to_string()
+  }
+
+  fn other_pid() -> String {
+    (std::process::id() + 1).to_string()
+  }
+
+  #[test]
+  fn notifier_sends_to_notify_socket() {
+    let dir = env::temp_dir().join(format!("trainer-notify-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("notify.sock");
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+      .set_read_timeout(Some(Duration::from_secs(5)))
+      .unwrap();
+    let mut notifier = Notifier::new(path.to_str(), Some("1"), Some(&own_pid()));
+
+    notifier.ready("serving on 127.0.0.1:12002");
+    assert_eq!(
+      receive(&socket),
+      "READY=1\nSTATUS=serving on 127.0.0.1:12002"
+    );
+    notifier.watchdog();
+    assert_eq!(receive(&socket), "WATCHDOG=1");
+    notifier.stopping();
+    assert_eq!(receive(&socket), "STOPPING=1");
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn notifier_sends_to_abstract_socket() {
+    use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+
+    let name = format!("trainer-notify-{}", std::process::id());
+    let socket = UnixDatagram::bind_addr(&SocketAddr::from_abstract_name(&name).unwrap()).unwrap();
+    socket
+      .set_read_timeout(Some(Duration::from_secs(5)))
+      .unwrap();
+    let notifier = Notifier::new(Some(&format!("@{}", name)), None, None);
+
+    notifier.stopping();
+    assert_eq!(receive(&socket), "STOPPING=1");
+  }
+
+  #[test]
+  fn notifier_watchdog_is_for_this_process_only() {
+    let notifier = Notifier::new(None, Some("1000000"), Some(&other_pid()));
+    assert!(notifier.socket.is_none());
+    assert_eq!(notifier.watchdog, None);
+
+    let notifier = Notifier::new(None, Some("1000000"), None);
+    assert_eq!(notifier.watchdog, Some(Duration::from_secs(1)));
+    let notifier = Notifier::new(Some(""), Some("0"), Some(&own_pid()));
+    assert!(notifier.socket.is_none());
+    assert_eq!(notifier.watchdog, None);
+    // Without a socket every message is dropped.
+    notifier.ready("serving");
+  }
+
+  #[test]
+  fn listen_socket_ignores_other_processes() {
+    assert!(
+      listen_socket_from(Some(&other_pid()), Some("1"))
+        .unwrap()
+        .is_none()
+    );
+    assert!(listen_socket_from(None, Some("1")).unwrap().is_none());
+    assert!(
+      listen_socket_from(Some(&own_pid()), None)
+        .unwrap()
+        .is_none()
+    );
+    assert!(
+      listen_socket_from(Some(&own_pid()), Some("0"))
+        .unwrap()
+        .is_none()
+    );
+    assert!(listen_socket_from(Some(&own_pid()), Some("two")).is_err());
+  }
+}
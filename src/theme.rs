@@ -0,0 +1,253 @@
+/* use  **************************************************************************************************/
+
+use crate::render::Style;
+use crate::timeline::Phase;
+use clap::ValueEnum;
+use crossterm::style::{self, Color};
+use std::{env, ffi::OsString};
+
+/* enum  *************************************************************************************************/
+
+/// `--theme` for the full-screen terminal UI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ThemeName {
+  /// The web page's colors: teal DOWN, red HOLD, blue UP.
+  Paper,
+  /// Bright colors for dark terminals.
+  Neon,
+  /// No colors, only bold; what `NO_COLOR` picks.
+  Mono,
+}
+
+/// How many colors the terminal takes; RGB theme colors are mapped down to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorDepth {
+  TrueColor,
+  Ansi256,
+  /// The 16 standard colors, whose exact shades the terminal decides.
+  Basic,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// A theme color and the standard color to use on 16-color terminals.
+#[derive(Clone, Copy, Debug)]
+struct Swatch {
+  rgb: (u8, u8, u8),
+  basic: Color,
+}
+
+/// Styles for the parts of the terminal frame.
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+  pub down: Style,
+  pub hold: Style,
+  pub up: Style,
+  pub rest: Style,
+  /// SET and TOTAL bars.
+  pub progress: Style,
+  /// The unfilled part of the bars.
+  pub track: Style,
+  /// Labels and the controls line.
+  pub muted: Style,
+}
+
+/* impl  *************************************************************************************************/
+
+impl ColorDepth {
+  /// What crossterm's `available_color_count` reads: `COLORTERM`, else `TERM`.
+  fn detect(lookup: impl Fn(&str) -> Option<OsString>) -> Self {
+    let var = |key: &str| lookup(key).map(|value| value.to_string_lossy().into_owned());
+    match var("COLORTERM").or_else(|| var("TERM")) {
+      Some(term) if term.contains("24bit") || term.contains("truecolor") => ColorDepth::TrueColor,
+      Some(term) if term.contains("256") => ColorDepth::Ansi256,
+      _ => ColorDepth::Basic,
+    }
+  }
+
+  fn color(self, swatch: Swatch) -> Color {
+    let (r, g, b) = swatch.rgb;
+    match self {
+      ColorDepth::TrueColor => Color::Rgb { r, g, b },
+      ColorDepth::Ansi256 => Color::AnsiValue(ansi256(r, g, b)),
+      ColorDepth::Basic => swatch.basic,
+    }
+  }
+}
+
+impl Theme {
+  /// `--theme` wins over `NO_COLOR`; without either it's `paper`.
+  pub fn resolve(name: Option<ThemeName>) -> Self {
+    if let Some(name) = name {
+      // crossterm drops colors by itself when NO_COLOR is set.
+      style::force_color_output(name != ThemeName::Mono);
+    }
+    let lookup = |key: &str| env::var_os(key);
+    Self::new(pick(name, lookup), ColorDepth::detect(lookup))
+  }
+
+  fn new(name: ThemeName, depth: ColorDepth) -> Self {
+    let fg = |rgb, basic| Style {
+      fg: Some(depth.color(Swatch { rgb, basic })),
+      bold: false,
+    };
+    let bold = |style: Style| Style {
+      bold: true,
+      ..style
+    };
+    match name {
+      ThemeName::Paper => Self {
+        down: bold(fg((47, 111, 109), Color::DarkCyan)),
+        hold: bold(fg((194, 74, 58), Color::DarkRed)),
+        up: bold(fg((60, 94, 137), Color::DarkBlue)),
+        rest: bold(fg((176, 132, 48), Color::DarkYellow)),
+        progress: fg((194, 74, 58), Color::DarkRed),
+        track: fg((140, 132, 120), Color::DarkGrey),
+        muted: fg((140, 132, 120), Color::DarkGrey),
+      },
+      ThemeName::Neon => Self {
+        down: bold(fg((64, 224, 208), Color::Cyan)),
+        hold: bold(fg((255, 95, 135), Color::Magenta)),
+        up: bold(fg((120, 170, 255), Color::Blue)),
+        rest: bold(fg((255, 215, 95), Color::Yellow)),
+        progress: fg((135, 255, 135), Color::Green),
+        track: fg((88, 88, 88), Color::DarkGrey),
+        muted: fg((138, 138, 138), Color::Grey),
+      },
+      ThemeName::Mono => Self {
+        down: bold(Style::default()),
+        hold: bold(Style::default()),
+        up: bold(Style::default()),
+        rest: bold(Style::default()),
+        progress: Style::default(),
+        track: Style::default(),
+        muted: Style::default(),
+      },
+    }
+  }
+
+  pub fn phase(&self, phase: Phase) -> Style {
+    match phase {
+      Phase::Down => self.down,
+      Phase::Hold => self.hold,
+      Phase::Up => self.up,
+      Phase::Rest => self.rest,
+    }
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+fn pick(name: Option<ThemeName>, lookup: impl Fn(&str) -> Option<OsString>) -> ThemeName {
+  match name {
+    Some(name) => name,
+    None if lookup("NO_COLOR").is_some_and(|value| !value.is_empty()) => ThemeName::Mono,
+    None => ThemeName::Paper,
+  }
+}
+
+/// Nearest entry of the 6×6×6 cube in the 256-color palette.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+  let level = |value: u8| -> u8 {
+    match value {
+      0..=47 => 0,
+      48..=114 => 1,
+      _ => (value - 35) / 40,
+    }
+  };
+  16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+    let vars: Vec<(String, String)> = vars
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect();
+    move |key| {
+      vars
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| OsString::from(value))
+    }
+  }
+
+  #[test]
+  fn ansi256_picks_the_nearest_cube_entry() {
+    assert_eq!(ansi256(0, 0, 0), 16);
+    assert_eq!(ansi256(255, 255, 255), 231);
+    assert_eq!(ansi256(255, 0, 0), 196);
+    // xterm's 67 is exactly #5f87af.
+    assert_eq!(ansi256(95, 135, 175), 67);
+    // Paper's DOWN teal.
+    assert_eq!(ansi256(47, 111, 109), 23);
+    // Level boundaries: 0 / 95 / 135 split at 48 and 115.
+    assert_eq!(ansi256(47, 48, 114), 16 + 6 + 1);
+    assert_eq!(ansi256(115, 154, 155), 16 + 36 * 2 + 6 * 2 + 3);
+  }
+
+  #[test]
+  fn color_depth_from_colorterm_and_term() {
+    let detect = |vars: &[(&str, &str)]| ColorDepth::detect(lookup(vars));
+    assert_eq!(detect(&[("COLORTERM", "truecolor")]), ColorDepth::TrueColor);
+    assert_eq!(detect(&[("COLORTERM", "24bit")]), ColorDepth::TrueColor);
+    assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+    assert_eq!(detect(&[("TERM", "xterm")]), ColorDepth::Basic);
+    assert_eq!(detect(&[]), ColorDepth::Basic);
+    // COLORTERM wins when both are set, like in crossterm.
+    assert_eq!(
+      detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm-256color")]),
+      ColorDepth::TrueColor
+    );
+  }
+
+  #[test]
+  fn color_depth_maps_swatches() {
+    let swatch = Swatch {
+      rgb: (194, 74, 58),
+      basic: Color::DarkRed,
+    };
+    assert_eq!(
+      ColorDepth::TrueColor.color(swatch),
+      Color::Rgb {
+        r: 194,
+        g: 74,
+        b: 58
+      }
+    );
+    assert_eq!(
+      ColorDepth::Ansi256.color(swatch),
+      Color::AnsiValue(16 + 36 * 3 + 6 + 1)
+    );
+    assert_eq!(ColorDepth::Basic.color(swatch), Color::DarkRed);
+  }
+
+  #[test]
+  fn no_color_picks_mono() {
+    assert_eq!(pick(None, lookup(&[("NO_COLOR", "1")])), ThemeName::Mono);
+    // An empty NO_COLOR doesn't count.
+    assert_eq!(pick(None, lookup(&[("NO_COLOR", "")])), ThemeName::Paper);
+    assert_eq!(pick(None, lookup(&[])), ThemeName::Paper);
+    // --theme wins.
+    assert_eq!(
+      pick(Some(ThemeName::Neon), lookup(&[("NO_COLOR", "1")])),
+      ThemeName::Neon
+    );
+  }
+
+  #[test]
+  fn mono_has_no_colors() {
+    let theme = Theme::new(ThemeName::Mono, ColorDepth::TrueColor);
+    for phase in [Phase::Down, Phase::Hold, Phase::Up, Phase::Rest] {
+      assert_eq!(theme.phase(phase).fg, None);
+      assert!(theme.phase(phase).bold);
+    }
+    assert_eq!(theme.progress.fg, None);
+    assert_eq!(theme.muted.fg, None);
+  }
+}
@@ -0,0 +1,647 @@
+/* use  **************************************************************************************************/
+
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, time::Duration};
+
+/* global const  *****************************************************************************************/
+
+pub const HOLD_SECS: f64 = 5.0;
+pub const DEFAULT_EXERCISE: &str = "Slow Squat";
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Phase {
+  Down,
+  Hold,
+  Up,
+  Rest,
+}
+
+/* struct  ***********************************************************************************************/
+
+/// Per-rep tempo in seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Tempo {
+  pub down: f64,
+  pub hold: f64,
+  pub up: f64,
+}
+
+/// One set of identical reps, followed by `rest_after` before the next set.
+#[derive(Clone, Debug)]
+pub struct SetSpec {
+  pub exercise: String,
+  pub reps: u32,
+  pub tempo: Tempo,
+  pub rest_after: Duration,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSpan {
+  pub exercise: String,
+  pub start: f64,
+  pub duration: f64,
+  pub reps: u32,
+  pub reps_before: u32,
+  pub down: f64,
+  pub hold: f64,
+  pub up: f64,
+}
+
+/// A single DOWN/HOLD/UP/REST stretch of the workout.
+///
+/// Everything a frontend shows is worked out here, so the terminal and the web page only
+/// look up the segment and interpolate the `[start, end]` pairs over it.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+  pub phase: Phase,
+  /// 0-based set the segment belongs to; during REST the one just finished.
+  pub set: u32,
+  /// 0-based rep in `set`; `reps` during REST.
+  pub rep: u32,
+  pub start: f64,
+  pub duration: f64,
+  /// 1-based set shown to the user; during REST the upcoming one.
+  pub shown_set: u32,
+  /// 1-based rep shown to the user, 0 during REST.
+  pub shown_rep: u32,
+  pub reps: u32,
+  /// Set whose exercise is shown; during REST the upcoming one.
+  pub exercise_set: u32,
+  pub completed_reps: u32,
+  pub completed_sets: u32,
+  pub depth: [f64; 2],
+  pub set_progress: [f64; 2],
+  /// Time under tension, which stands still during REST.
+  pub active: [f64; 2],
+}
+
+/// The full schedule of a workout, shared by the terminal and web frontends.
+///
+/// All times are seconds from the start of the first rep, pauses excluded.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkoutTimeline {
+  total: f64,
+  active_total: f64,
+  sets: Vec<SetSpan>,
+  segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TimelineState {
+  /// Exercise of the current set; during REST the upcoming one.
+  pub exercise: String,
+  pub phase: Phase,
+  /// 1-based set shown to the user; during REST this is the upcoming set.
+  pub set: u32,
+  pub sets: u32,
+  /// 1-based rep in the current set, 0 during REST.
+  pub rep: u32,
+  pub reps: u32,
+  /// Reps finished across the whole workout.
+  pub total_completed_reps: u32,
+  /// Sets whose reps are all finished.
+  pub completed_sets: u32,
+  pub tempo: Tempo,
+  /// 0.0 = standing, 1.0 = bottom of the squat.
+  pub depth: f64,
+  /// 0.0–1.0 through the current DOWN/HOLD/UP/REST.
+  pub phase_progress: f64,
+  /// 0.0–1.0 through the reps of the current set; 1.0 during REST.
+  pub set_progress: f64,
+  /// 0.0–1.0 through the whole workout, rests included.
+  pub progress: f64,
+  pub rest_remaining: Duration,
+  pub remaining: Duration,
+  /// Time spent in DOWN/HOLD/UP, i.e. time under tension.
+  pub active_elapsed: Duration,
+  pub done: bool,
+}
+
+/* impl standard traits  *********************************************************************************/
+
+impl fmt::Display for Phase {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.label())
+  }
+}
+
+impl FromStr for Tempo {
+  type Err = String;
+
+  /// Parses a `DOWN-HOLD-UP` spec such as `4-2-1` or `3.5-5-3.5`.
+  fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+    let parts = value
+      .split('-')
+      .map(|part| part.trim().parse::<f64>())
+      .collect::<std::result::Result<Vec<_>, _>>()
+      .map_err(|err| format!("invalid tempo {:?}: {}", value, err))?;
+    let [down, hold, up] = parts[..] else {
+      return Err(format!(
+        "invalid tempo {:?}: expected DOWN-HOLD-UP such as 4-2-1",
+        value
+      ));
+    };
+    let tempo = Self { down, hold, up };
+    tempo.validate().map_err(|err| err.to_string())?;
+    Ok(tempo)
+  }
+}
+
+/* impl  *************************************************************************************************/
+
+impl Phase {
+  pub fn label(self) -> &'static str {
+    match self {
+      Phase::Down => "DOWN",
+      Phase::Hold => "HOLD",
+      Phase::Up => "UP",
+      Phase::Rest => "REST",
+    }
+  }
+}
+
+impl Tempo {
+  /// Splits a rep evenly into down and up around a fixed hold.
+  pub fn from_rep(rep_secs: f64, hold: f64) -> Result<Self> {
+    if rep_secs <= hold {
+      return Err(eyre!(
+        "duration/count is {:.1}s; it must be greater than the {:.1}s hold",
+        rep_secs,
+        hold
+      ));
+    }
+    let move_secs = (rep_secs - hold) / 2.0;
+    Ok(Self {
+      down: move_secs,
+      hold,
+      up: move_secs,
+    })
+  }
+
+  /// Fills in whichever of down/up is missing from the remaining rep time.
+  pub fn from_rep_partial(
+    rep_secs: f64,
+    hold: f64,
+    down: Option<f64>,
+    up: Option<f64>,
+  ) -> Result<Self> {
+    let tempo = match (down, up) {
+      (Some(down), Some(up)) => Self { down, hold, up },
+      (Some(down), None) => {
+        if rep_secs <= hold + down {
+          return Err(eyre!(
+            "duration/count must be greater than {:.1}s to allow a {:.1}s down and {:.1}s hold",
+            hold + down,
+            down,
+            hold
+          ));
+        }
+        Self {
+          down,
+          hold,
+          up: rep_secs - hold - down,
+        }
+      }
+      (None, Some(up)) => {
+        if rep_secs <= hold + up {
+          return Err(eyre!(
+            "duration/count must be greater than {:.1}s to allow a {:.1}s hold and {:.1}s up",
+            hold + up,
+            hold,
+            up
+          ));
+        }
+        Self {
+          down: rep_secs - hold - up,
+          hold,
+          up,
+        }
+      }
+      (None, None) => Self::from_rep(rep_secs, hold)?,
+    };
+    tempo.validate()?;
+    Ok(tempo)
+  }
+
+  pub fn validate(&self) -> Result<()> {
+    if !(self.down.is_finite() && self.hold.is_finite() && self.up.is_finite()) {
+      return Err(eyre!("tempo values must be finite numbers"));
+    }
+    if self.down <= 0.0 || self.up <= 0.0 {
+      return Err(eyre!("down and up must be > 0s"));
+    }
+    if self.hold < 0.0 {
+      return Err(eyre!("hold must be >= 0s"));
+    }
+    Ok(())
+  }
+}
+
+impl WorkoutTimeline {
+  pub fn new(specs: &[SetSpec]) -> Self {
+    let mut sets = Vec::with_capacity(specs.len());
+    let mut segments = Vec::new();
+    let mut cursor = 0.0;
+    let mut active = 0.0;
+    let mut reps_before = 0;
+
+    for (set_idx, spec) in specs.iter().enumerate() {
+      let set = set_idx as u32;
+      let set_start = cursor;
+      let first_segment = segments.len();
+      for rep in 0..spec.reps {
+        for (phase, duration, depth) in [
+          (Phase::Down, spec.tempo.down, [0.0, 1.0]),
+          (Phase::Hold, spec.tempo.hold, [1.0, 1.0]),
+          (Phase::Up, spec.tempo.up, [1.0, 0.0]),
+        ] {
+          if duration <= 0.0 {
+            continue;
+          }
+          segments.push(Segment {
+            phase,
+            set,
+            rep,
+            start: cursor,
+            duration,
+            shown_set: set + 1,
+            shown_rep: rep + 1,
+            reps: spec.reps,
+            exercise_set: set,
+            completed_reps: reps_before + rep,
+            completed_sets: set,
+            depth,
+            set_progress: [0.0, 0.0],
+            active: [active, active + duration],
+          });
+          cursor += duration;
+          active += duration;
+        }
+      }
+      let set_duration = cursor - set_start;
+      for segment in &mut segments[first_segment..] {
+        let end = segment.start + segment.duration;
+        segment.set_progress = [
+          (segment.start - set_start) / set_duration,
+          (end - set_start) / set_duration,
+        ];
+      }
+      sets.push(SetSpan {
+        exercise: spec.exercise.clone(),
+        start: set_start,
+        duration: set_duration,
+        reps: spec.reps,
+        reps_before,
+        down: spec.tempo.down,
+        hold: spec.tempo.hold,
+        up: spec.tempo.up,
+      });
+      reps_before += spec.reps;
+
+      let rest = spec.rest_after.as_secs_f64();
+      if set_idx + 1 < specs.len() && rest > 0.0 {
+        segments.push(Segment {
+          phase: Phase::Rest,
+          set,
+          rep: spec.reps,
+          start: cursor,
+          duration: rest,
+          shown_set: set + 2,
+          shown_rep: 0,
+          reps: spec.reps,
+          exercise_set: set + 1,
+          completed_reps: reps_before,
+          completed_sets: set + 1,
+          depth: [0.0, 0.0],
+          set_progress: [1.0, 1.0],
+          active: [active, active],
+        });
+        cursor += rest;
+      }
+    }
+
+    Self {
+      total: cursor,
+      active_total: active,
+      sets,
+      segments,
+    }
+  }
+
+  /// `sets` identical sets of `count` reps at `tempo`, with `interval` between sets.
+  pub fn uniform(tempo: Tempo, count: u32, sets: u32, interval: Duration) -> Self {
+    let spec = SetSpec {
+      exercise: DEFAULT_EXERCISE.to_string(),
+      reps: count,
+      tempo,
+      rest_after: interval,
+    };
+    Self::new(&vec![spec; sets as usize])
+  }
+
+  pub fn sets(&self) -> &[SetSpan] {
+    &self.sets
+  }
+
+  pub fn total_reps(&self) -> u32 {
+    self.sets.iter().map(|set| set.reps).sum()
+  }
+
+  pub fn at(&self, elapsed: Duration) -> TimelineState {
+    let t = elapsed.as_secs_f64();
+    let set_count = self.sets.len() as u32;
+    let remaining = Duration::from_secs_f64((self.total - t).max(0.0));
+
+    if t >= self.total || self.segments.is_empty() {
+      let last = self.sets.last();
+      let reps = last.map_or(0, |span| span.reps);
+      return TimelineState {
+        exercise: last.map_or_else(String::new, |span| span.exercise.clone()),
+        phase: Phase::Up,
+        set: set_count,
+        sets: set_count,
+        rep: reps,
+        reps,
+        total_completed_reps: self.total_reps(),
+        completed_sets: set_count,
+        tempo: last.map_or(
+          Tempo {
+            down: 0.0,
+            hold: 0.0,
+            up: 0.0,
+          },
+          SetSpan::tempo,
+        ),
+        depth: 0.0,
+        phase_progress: 1.0,
+        set_progress: 1.0,
+        progress: 1.0,
+        rest_remaining: Duration::ZERO,
+        remaining,
+        active_elapsed: Duration::from_secs_f64(self.active_total),
+        done: true,
+      };
+    }
+
+    let idx = self
+      .segments
+      .partition_point(|segment| segment.start <= t)
+      .saturating_sub(1);
+    let segment = &self.segments[idx];
+    let span = &self.sets[segment.set as usize];
+    let within = (t - segment.start).max(0.0);
+    let phase_progress = (within / segment.duration).clamp(0.0, 1.0);
+    let lerp = |[from, to]: [f64; 2]| from + (to - from) * phase_progress;
+
+    TimelineState {
+      exercise: self.sets[segment.exercise_set as usize].exercise.clone(),
+      phase: segment.phase,
+      set: segment.shown_set,
+      sets: set_count,
+      rep: segment.shown_rep,
+      reps: segment.reps,
+      total_completed_reps: segment.completed_reps,
+      completed_sets: segment.completed_sets,
+      tempo: span.tempo(),
+      depth: lerp(segment.depth),
+      phase_progress,
+      set_progress: lerp(segment.set_progress),
+      progress: (t / self.total).clamp(0.0, 1.0),
+      rest_remaining: if segment.phase == Phase::Rest {
+        Duration::from_secs_f64((segment.duration - within).max(0.0))
+      } else {
+        Duration::ZERO
+      },
+      remaining,
+      active_elapsed: Duration::from_secs_f64(lerp(segment.active)),
+      done: false,
+    }
+  }
+
+  pub fn total(&self) -> Duration {
+    Duration::from_secs_f64(self.total)
+  }
+
+  /// Where the rep after the first `completed` reps starts; a resumed workout picks up
+  /// there, skipping any rest in between.
+  pub fn rep_start(&self, completed: u32) -> Duration {
+    let start = self
+      .segments
+      .iter()
+      .find(|segment| {
+        segment.phase != Phase::Rest
+          && self.sets[segment.set as usize].reps_before + segment.rep == completed
+      })
+      .map_or(self.total, |segment| segment.start);
+    Duration::from_secs_f64(start)
+  }
+}
+
+impl SetSpan {
+  pub fn tempo(&self) -> Tempo {
+    Tempo {
+      down: self.down,
+      hold: self.hold,
+      up: self.up,
+    }
+  }
+}
+
+/* test for pri ******************************************************************************************/
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  /// Two sets of two 2-1-1 reps with 3s between them: set 1 is 0–8s, REST 8–11s,
+  /// set 2 11–19s.
+  fn two_sets() -> WorkoutTimeline {
+    let tempo = Tempo {
+      down: 2.0,
+      hold: 1.0,
+      up: 1.0,
+    };
+    WorkoutTimeline::uniform(tempo, 2, 2, Duration::from_secs(3))
+  }
+
+  fn at(timeline: &WorkoutTimeline, secs: f64) -> TimelineState {
+    timeline.at(Duration::from_secs_f64(secs))
+  }
+
+  fn assert_close(actual: f64, expected: f64) {
+    assert!(
+      (actual - expected).abs() < 1e-9,
+      "{} != {}",
+      actual,
+      expected
+    );
+  }
+
+  #[test]
+  fn at_follows_down_hold_up() {
+    let timeline = two_sets();
+
+    let start = at(&timeline, 0.0);
+    assert_eq!(
+      (start.phase, start.set, start.rep, start.reps),
+      (Phase::Down, 1, 1, 2)
+    );
+    assert_close(start.depth, 0.0);
+
+    let halfway_down = at(&timeline, 1.0);
+    assert_eq!(halfway_down.phase, Phase::Down);
+    assert_close(halfway_down.depth, 0.5);
+    assert_close(halfway_down.phase_progress, 0.5);
+
+    let hold = at(&timeline, 2.0);
+    assert_eq!(hold.phase, Phase::Hold);
+    assert_close(hold.depth, 1.0);
+
+    let halfway_up = at(&timeline, 3.5);
+    assert_eq!(halfway_up.phase, Phase::Up);
+    assert_close(halfway_up.depth, 0.5);
+
+    let second_rep = at(&timeline, 4.0);
+    assert_eq!(
+      (
+        second_rep.phase,
+        second_rep.rep,
+        second_rep.total_completed_reps
+      ),
+      (Phase::Down, 2, 1)
+    );
+    assert_close(second_rep.set_progress, 0.5);
+    assert_eq!(second_rep.active_elapsed, Duration::from_secs(4));
+  }
+
+  #[test]
+  fn at_shows_the_upcoming_set_during_rest() {
+    let timeline = two_sets();
+
+    let rest = at(&timeline, 8.0);
+    assert_eq!(rest.phase, Phase::Rest);
+    assert_eq!((rest.set, rest.sets, rest.rep), (2, 2, 0));
+    assert_eq!((rest.total_completed_reps, rest.completed_sets), (2, 1));
+    assert_eq!(rest.rest_remaining, Duration::from_secs(3));
+    assert_close(rest.set_progress, 1.0);
+    assert_close(rest.depth, 0.0);
+
+    let later = at(&timeline, 9.5);
+    assert_eq!(later.rest_remaining, Duration::from_millis(1500));
+    assert_eq!(later.active_elapsed, Duration::from_secs(8));
+
+    let next_set = at(&timeline, 11.0);
+    assert_eq!(
+      (next_set.phase, next_set.set, next_set.rep),
+      (Phase::Down, 2, 1)
+    );
+    assert_eq!(
+      (next_set.total_completed_reps, next_set.completed_sets),
+      (2, 1)
+    );
+    assert_eq!(next_set.rest_remaining, Duration::ZERO);
+    assert_close(next_set.set_progress, 0.0);
+  }
+
+  #[test]
+  fn at_rest_names_the_next_exercise() {
+    let tempo = Tempo {
+      down: 1.0,
+      hold: 1.0,
+      up: 1.0,
+    };
+    let spec = |exercise: &str| SetSpec {
+      exercise: exercise.to_string(),
+      reps: 1,
+      tempo,
+      rest_after: Duration::from_secs(2),
+    };
+    let timeline = WorkoutTimeline::new(&[spec("Squat"), spec("Lunge")]);
+    assert_eq!(at(&timeline, 1.0).exercise, "Squat");
+    assert_eq!(at(&timeline, 3.5).exercise, "Lunge");
+    assert_eq!(at(&timeline, 3.5).tempo, tempo);
+  }
+
+  #[test]
+  fn at_the_end_is_done() {
+    let timeline = two_sets();
+    for secs in [19.0, 100.0] {
+      let done = at(&timeline, secs);
+      assert!(done.done);
+      assert_eq!((done.set, done.rep), (2, 2));
+      assert_eq!((done.total_completed_reps, done.completed_sets), (4, 2));
+      assert_eq!(done.remaining, Duration::ZERO);
+      assert_eq!(done.active_elapsed, Duration::from_secs(16));
+      assert_close(done.progress, 1.0);
+    }
+    assert!(!at(&timeline, 18.9).done);
+  }
+
+  #[test]
+  fn rep_start_skips_the_rest() {
+    let timeline = two_sets();
+    assert_eq!(timeline.total(), Duration::from_secs(19));
+    assert_eq!(timeline.total_reps(), 4);
+    let starts: Vec<Duration> = (0..=4)
+      .map(|completed| timeline.rep_start(completed))
+      .collect();
+    assert_eq!(starts, [0, 4, 11, 15, 19].map(Duration::from_secs));
+  }
+
+  #[test]
+  fn tempo_from_str() {
+    let tempo: Tempo = "4-2-1".parse().unwrap();
+    assert_eq!(
+      tempo,
+      Tempo {
+        down: 4.0,
+        hold: 2.0,
+        up: 1.0
+      }
+    );
+    assert_eq!(" 3.5 - 0 - 3.5".parse::<Tempo>().unwrap().hold, 0.0);
+    for bad in ["", "4-2", "4-2-1-1", "a-2-1", "0-2-1", "4--1", "4-2-inf"] {
+      assert!(bad.parse::<Tempo>().is_err(), "{:?} parsed", bad);
+    }
+  }
+
+  #[test]
+  fn tempo_from_rep_partial_fills_the_missing_side() {
+    let down_fixed = Tempo::from_rep_partial(10.0, 5.0, Some(2.0), None).unwrap();
+    assert_eq!((down_fixed.down, down_fixed.up), (2.0, 3.0));
+
+    let up_fixed = Tempo::from_rep_partial(10.0, 5.0, None, Some(1.0)).unwrap();
+    assert_eq!((up_fixed.down, up_fixed.up), (4.0, 1.0));
+
+    let even = Tempo::from_rep_partial(10.0, 4.0, None, None).unwrap();
+    assert_eq!((even.down, even.up), (3.0, 3.0));
+
+    assert!(Tempo::from_rep_partial(10.0, 5.0, Some(5.0), None).is_err());
+    assert!(Tempo::from_rep_partial(10.0, 5.0, None, Some(6.0)).is_err());
+    assert!(Tempo::from_rep_partial(4.0, 5.0, None, None).is_err());
+  }
+
+  #[test]
+  fn from_rep_splits_the_moves() {
+    let tempo = Tempo::from_rep(6.0, 2.0).unwrap();
+    assert_eq!((tempo.down, tempo.hold, tempo.up), (2.0, 2.0, 2.0));
+  }
+
+  #[test]
+  fn from_rep_reports_the_rep_time() {
+    let err = Tempo::from_rep(1.5, 2.0).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "duration/count is 1.5s; it must be greater than the 2.0s hold"
+    );
+  }
+}
@@ -0,0 +1,281 @@
+/* use  **************************************************************************************************/
+
+use std::io::{self, Cursor, Write};
+use tiny_http::{Header, Method, Request, Response};
+
+/* type alias  *******************************************************************************************/
+
+pub type HttpResponse = Response<Cursor<Vec<u8>>>;
+type Handler<'a> = Box<dyn Fn(&mut Request) -> HttpResponse + Send + Sync + 'a>;
+type StreamHandler<'a> = Box<dyn Fn(Request) + Send + Sync + 'a>;
+
+/* global const  *****************************************************************************************/
+
+pub const CONTENT_HTML: &str = "text/html; charset=utf-8";
+pub const CONTENT_JSON: &str = "application/json";
+pub const CONTENT_TEXT: &str = "text/plain; charset=utf-8";
+
+/* enum  *************************************************************************************************/
+
+enum Action<'a> {
+  Respond(Handler<'a>),
+  /// Takes over the connection, e.g. for a long-lived event stream.
+  Stream(StreamHandler<'a>),
+}
+
+/* struct  ***********************************************************************************************/
+
+struct Route<'a> {
+  method: Method,
+  path: &'static str,
+  action: Action<'a>,
+}
+
+/// Exact-path router; unknown paths get 404 and known paths with the wrong method get 405.
+#[derive(Default)]
+pub struct Router<'a> {
+  routes: Vec<Route<'a>>,
+}
+
+/// A `text/event-stream` response written straight to the connection.
+pub struct EventStream {
+  writer: Box<dyn Write + Send>,
+}
+
+/* impl  *************************************************************************************************/
+
+impl<'a> Router<'a> {
+  pub fn new() -> Self {
+    Self { routes: Vec::new() }
+  }
+
+  pub fn route<F>(mut self, method: Method, path: &'static str, handler: F) -> Self
+  where
+    F: Fn(&mut Request) -> HttpResponse + Send + Sync + 'a,
+  {
+    self.routes.push(Route {
+      method,
+      path,
+      action: Action::Respond(Box::new(handler)),
+    });
+    self
+  }
+
+  pub fn stream<F>(mut self, method: Method, path: &'static str, handler: F) -> Self
+  where
+    F: Fn(Request) + Send + Sync + 'a,
+  {
+    self.routes.push(Route {
+      method,
+      path,
+      action: Action::Stream(Box::new(handler)),
+    });
+    self
+  }
+
+  pub fn handle(&self, mut request: Request) {
+    let path = request_path(&request).to_string();
+    let method = request.method().clone();
+    let mut allowed = Vec::new();
+    let mut response = None;
+    for route in self.routes.iter().filter(|route| route.path == path) {
+      if route.method == method || (method == Method::Head && route.method == Method::Get) {
+        match &route.action {
+          Action::Respond(handler) => response = Some(handler(&mut request)),
+          Action::Stream(handler) => {
+            tracing::debug!("{} {} -> stream", method, path);
+            handler(request);
+            return;
+          }
+        }
+        break;
+      }
+      allowed.push(route.method.as_str());
+      if route.method == Method::Get {
+        allowed.push("HEAD");
+      }
+    }
+    let response = response.unwrap_or_else(|| {
+      if allowed.is_empty() {
+        text(404, "not found")
+      } else {
+        let allow = allowed.join(", ");
+        with_header(text(405, "method not allowed"), "Allow", &allow)
+      }
+    });
+    tracing::debug!("{} {} -> {}", method, path, response.status_code().0);
+    let _ = request.respond(response);
+  }
+}
+
+impl EventStream {
+  pub fn open(request: Request) -> io::Result<Self> {
+    let mut writer = request.into_writer();
+    writer.write_all(
+      b"HTTP/1.1 200 OK\r\n\
+        Content-Type: text/event-stream\r\n\
+        Cache-Control: no-cache\r\n\
+        Connection: keep-alive\r\n\
+        \r\n\
+        retry: 2000\n\n",
+    )?;
+    writer.flush()?;
+    Ok(Self::from_writer(writer))
+  }
+
+  /// Events written to `writer` after the response head has gone out.
+  pub fn from_writer(writer: Box<dyn Write + Send>) -> Self {
+    Self { writer }
+  }
+
+  pub fn send(&mut self, event: &str, data: &str) -> io::Result<()> {
+    writeln!(self.writer, "event: {}", event)?;
+    for line in data.lines() {
+      writeln!(self.writer, "data: {}", line)?;
+    }
+    self.writer.write_all(b"\n")?;
+    self.writer.flush()
+  }
+
+  /// Comment line; keeps proxies from timing out and surfaces closed connections.
+  pub fn ping(&mut self) -> io::Result<()> {
+    self.writer.write_all(b": ping\n\n")?;
+    self.writer.flush()
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+pub fn request_path(request: &Request) -> &str {
+  let url = request.url();
+  url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+/// Raw query string without the leading `?`.
+pub fn request_query(request: &Request) -> &str {
+  request.url().split_once('?').map_or("", |(_, query)| query)
+}
+
+/// Decoded `key=value` pairs of the query string, in order.
+pub fn query_params(request: &Request) -> Vec<(String, String)> {
+  parse_query(request_query(request))
+}
+
+/// The raw query string with every `key` parameter taken out.
+pub fn query_without(query: &str, key: &str) -> String {
+  query
+    .split('&')
+    .filter(|pair| {
+      !pair.is_empty() && percent_decode(pair.split_once('=').map_or(pair, |(name, _)| name)) != key
+    })
+    .collect::<Vec<_>>()
+    .join("&")
+}
+
+/// `a=1&b=x+y` → pairs in order, repeated keys included.
+fn parse_query(query: &str) -> Vec<(String, String)> {
+  query
+    .split('&')
+    .filter(|pair| !pair.is_empty())
+    .map(|pair| {
+      let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+      (percent_decode(key), percent_decode(value))
+    })
+    .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut out = Vec::with_capacity(bytes.len());
+  let mut idx = 0;
+  while idx < bytes.len() {
+    match bytes[idx] {
+      b'+' => out.push(b' '),
+      b'%' if idx + 2 < bytes.len() => {
+        let hex = |byte: u8| (byte as char).to_digit(16);
+        match (hex(bytes[idx + 1]), hex(bytes[idx + 2])) {
+          (Some(high), Some(low)) => {
+            out.push((high * 16 + low) as u8);
+            idx += 2;
+          }
+          _ => out.push(b'%'),
+        }
+      }
+      byte => out.push(byte),
+    }
+    idx += 1;
+  }
+  String::from_utf8_lossy(&out).into_owned()
+}
+
+pub fn with_header(response: HttpResponse, name: &str, value: &str) -> HttpResponse {
+  match Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+    Ok(header) => response.with_header(header),
+    Err(_) => response,
+  }
+}
+
+pub fn body(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> HttpResponse {
+  let response = Response::from_data(body.into()).with_status_code(status);
+  with_header(response, "Content-Type", content_type)
+}
+
+pub fn html(body_text: impl Into<Vec<u8>>) -> HttpResponse {
+  body(200, CONTENT_HTML, body_text)
+}
+
+pub fn json(status: u16, body_text: impl Into<Vec<u8>>) -> HttpResponse {
+  body(status, CONTENT_JSON, body_text)
+}
+
+pub fn text(status: u16, body_text: impl Into<Vec<u8>>) -> HttpResponse {
+  body(status, CONTENT_TEXT, body_text)
+}
+
+pub fn no_content() -> HttpResponse {
+  Response::from_data(Vec::new()).with_status_code(204)
+}
+
+/* test for pri ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pri {
+  use super::*;
+
+  #[test]
+  fn percent_decode_handles_plus_and_escapes() {
+    assert_eq!(percent_decode("a+b%20c"), "a b c");
+    assert_eq!(percent_decode("4-2-1%2B"), "4-2-1+");
+    assert_eq!(percent_decode("%E3%82%B9%e3%82%af"), "スク");
+    assert_eq!(percent_decode("スクワット"), "スクワット");
+  }
+
+  #[test]
+  fn percent_decode_keeps_bad_escapes() {
+    assert_eq!(percent_decode("%zz"), "%zz");
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(percent_decode("%4"), "%4");
+    assert_eq!(percent_decode("%%41"), "%A");
+    assert_eq!(percent_decode("%FF"), "\u{FFFD}");
+  }
+
+  #[test]
+  fn parse_query_keeps_order_and_repeated_keys() {
+    let pairs = parse_query("count=12&&sets=3&count=8&flag&name=Slow+Squat&t%61g=%E2%9C%93");
+    let expected = [
+      ("count", "12"),
+      ("sets", "3"),
+      ("count", "8"),
+      ("flag", ""),
+      ("name", "Slow Squat"),
+      ("tag", "✓"),
+    ];
+    assert_eq!(
+      pairs,
+      expected.map(|(key, value)| (key.to_string(), value.to_string()))
+    );
+    assert!(parse_query("").is_empty());
+  }
+}
+
+/* test for pub ******************************************************************************************/
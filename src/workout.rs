@@ -0,0 +1,344 @@
+/* use  **************************************************************************************************/
+
+use crate::figure::Tremor;
+use crate::timeline::{HOLD_SECS, Tempo, WorkoutTimeline};
+use clap::Args;
+use color_eyre::eyre::{Result, eyre};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+/* global const  *****************************************************************************************/
+
+/// Upper bound for reps requested through a query string, so one URL can't make the server
+/// build an enormous timeline.
+const MAX_QUERY_REPS: u64 = 10_000;
+
+/* enum  *************************************************************************************************/
+
+#[derive(Clone, Debug)]
+pub enum WorkoutSource {
+  Uniform(UniformWorkout),
+  Plan(WorkoutTimeline),
+}
+
+/* struct  ***********************************************************************************************/
+
+#[derive(Args, Clone, Debug, Default, Serialize)]
+pub struct TempoArgs {
+  /// Hold time at the bottom in seconds [default: 5.0]
+  #[arg(long, value_parser = clap::value_parser!(f64))]
+  pub hold: Option<f64>,
+  /// Down time in seconds (default: split the rest of the rep evenly)
+  #[arg(long, value_parser = clap::value_parser!(f64))]
+  pub down: Option<f64>,
+  /// Up time in seconds (default: split the rest of the rep evenly)
+  #[arg(long, value_parser = clap::value_parser!(f64))]
+  pub up: Option<f64>,
+  /// DOWN-HOLD-UP seconds, e.g. 4-2-1; overrides --duration
+  #[arg(long, value_parser = clap::value_parser!(Tempo), conflicts_with_all = ["hold", "down", "up"])]
+  pub tempo: Option<Tempo>,
+}
+
+/// `sets` identical sets described by CLI flags or query parameters.
+#[derive(Clone, Debug)]
+pub struct UniformWorkout {
+  pub duration: u64,
+  pub count: u32,
+  pub sets: u32,
+  pub interval: u64,
+  pub tempo: TempoArgs,
+}
+
+/// Everything the web page needs to run a workout.
+#[derive(Clone, Debug)]
+pub struct WebWorkout {
+  pub title: String,
+  pub source: WorkoutSource,
+  pub swing_start: f64,
+  pub swing_stop: f64,
+  pub freq: f64,
+  /// Group class: every page shares one session of the server's workout, so queries
+  /// can't change it.
+  pub group: bool,
+}
+
+/* impl  *************************************************************************************************/
+
+impl TempoArgs {
+  /// Resolves the per-rep tempo; `--down` + `--up` (or `--tempo`) fix the rep length and
+  /// make `--duration` irrelevant, otherwise the rep is `duration / count`.
+  pub fn resolve(&self, duration: u64, count: u32) -> Result<Tempo> {
+    if let Some(tempo) = self.tempo {
+      if self.hold.is_some() || self.down.is_some() || self.up.is_some() {
+        return Err(eyre!("tempo cannot be combined with hold, down or up"));
+      }
+      return Ok(tempo);
+    }
+    let hold = self.hold.unwrap_or(HOLD_SECS);
+    let rep_secs = duration as f64 / count as f64;
+    Tempo::from_rep_partial(rep_secs, hold, self.down, self.up)
+  }
+}
+
+impl UniformWorkout {
+  pub fn timeline(&self) -> Result<WorkoutTimeline> {
+    if self.duration == 0 || self.count == 0 || self.sets == 0 {
+      return Err(eyre!("duration, count and sets must be >= 1"));
+    }
+    let tempo = self.tempo.resolve(self.duration, self.count)?;
+    Ok(WorkoutTimeline::uniform(
+      tempo,
+      self.count,
+      self.sets,
+      Duration::from_secs(self.interval),
+    ))
+  }
+}
+
+impl WebWorkout {
+  pub fn validate(&self) -> Result<()> {
+    Tremor {
+      swing_start: self.swing_start,
+      swing_stop: self.swing_stop,
+      freq: self.freq,
+    }
+    .validate()?;
+    self.timeline().map(|_| ())
+  }
+
+  pub fn timeline(&self) -> Result<WorkoutTimeline> {
+    match &self.source {
+      WorkoutSource::Uniform(workout) => workout.timeline(),
+      WorkoutSource::Plan(timeline) => Ok(timeline.clone()),
+    }
+  }
+
+  /// Applies `?count=12&sets=3&interval=45`-style overrides and validates the result.
+  /// Tempo keys are merged into the server's: `hold`/`down`/`up` replace only themselves
+  /// (and drop a server `--tempo`), `tempo` replaces all three.
+  pub fn with_query(&self, query: &[(String, String)]) -> Result<Self> {
+    let mut next = self.clone();
+    let mut tempo_override = TempoArgs::default();
+    for (raw_key, value) in query {
+      let key = raw_key.replace('-', "_");
+      match key.as_str() {
+        "swing_start" => next.swing_start = parse_param(&key, value)?,
+        "swing_stop" => next.swing_stop = parse_param(&key, value)?,
+        "freq" => next.freq = parse_param(&key, value)?,
+        "duration" | "count" | "sets" | "set" | "interval" | "hold" | "down" | "up" | "tempo" => {
+          if next.group {
+            return Err(eyre!("{} cannot be overridden in group mode", raw_key));
+          }
+          let WorkoutSource::Uniform(workout) = &mut next.source else {
+            return Err(eyre!(
+              "{} cannot be overridden when serving a plan",
+              raw_key
+            ));
+          };
+          match key.as_str() {
+            "duration" => workout.duration = parse_param(&key, value)?,
+            "count" => workout.count = parse_param(&key, value)?,
+            "sets" | "set" => workout.sets = parse_param(&key, value)?,
+            "interval" => workout.interval = parse_param(&key, value)?,
+            "hold" => tempo_override.hold = Some(parse_param(&key, value)?),
+            "down" => tempo_override.down = Some(parse_param(&key, value)?),
+            "up" => tempo_override.up = Some(parse_param(&key, value)?),
+            _ => tempo_override.tempo = Some(value.parse::<Tempo>().map_err(|err| eyre!(err))?),
+          }
+        }
+        _ => return Err(eyre!("unknown parameter {:?}", raw_key)),
+      }
+    }
+    if let WorkoutSource::Uniform(workout) = &mut next.source {
+      let parts_given = tempo_override.hold.is_some()
+        || tempo_override.down.is_some()
+        || tempo_override.up.is_some();
+      match tempo_override.tempo {
+        Some(_) if parts_given => {
+          return Err(eyre!("tempo cannot be combined with hold, down or up"));
+        }
+        Some(_) => workout.tempo = tempo_override,
+        None if parts_given => {
+          let tempo = &mut workout.tempo;
+          tempo.tempo = None;
+          tempo.hold = tempo_override.hold.or(tempo.hold);
+          tempo.down = tempo_override.down.or(tempo.down);
+          tempo.up = tempo_override.up.or(tempo.up);
+        }
+        None => {}
+      }
+      if u64::from(workout.count) * u64::from(workout.sets) > MAX_QUERY_REPS {
+        return Err(eyre!("count * sets must be <= {}", MAX_QUERY_REPS));
+      }
+    }
+    next.validate()?;
+    Ok(next)
+  }
+
+  pub fn to_json(&self, timeline: &WorkoutTimeline) -> String {
+    let mut value = json!({
+      "title": self.title,
+      "mode": "plan",
+      "swingStart": self.swing_start,
+      "swingStop": self.swing_stop,
+      "freq": self.freq,
+      "group": self.group,
+      "timeline": timeline,
+    });
+    if let WorkoutSource::Uniform(workout) = &self.source {
+      value["mode"] = json!("uniform");
+      value["duration"] = json!(workout.duration);
+      value["count"] = json!(workout.count);
+      value["sets"] = json!(workout.sets);
+      value["interval"] = json!(workout.interval);
+      value["tempo"] = json!(workout.tempo);
+    }
+    value.to_string()
+  }
+}
+
+/* fn  ***************************************************************************************************/
+
+fn parse_param<T>(key: &str, value: &str) -> Result<T>
+where
+  T: std::str::FromStr,
+  T::Err: std::fmt::Display,
+{
+  value
+    .trim()
+    .parse::<T>()
+    .map_err(|err| eyre!("invalid {} {:?}: {}", key, value, err))
+}
+
+/* test for pri ******************************************************************************************/
+
+/* test for pub ******************************************************************************************/
+
+#[cfg(test)]
+mod test_pub {
+  use super::*;
+
+  fn served(tempo: TempoArgs) -> WebWorkout {
+    WebWorkout {
+      title: "Slow Squat".to_string(),
+      source: WorkoutSource::Uniform(UniformWorkout {
+        duration: 300,
+        count: 20,
+        sets: 1,
+        interval: 60,
+        tempo,
+      }),
+      swing_start: 0.4,
+      swing_stop: 3.4,
+      freq: 10.0,
+      group: false,
+    }
+  }
+
+  fn query(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect()
+  }
+
+  fn uniform(workout: &WebWorkout) -> &UniformWorkout {
+    match &workout.source {
+      WorkoutSource::Uniform(workout) => workout,
+      WorkoutSource::Plan(_) => panic!("expected a uniform workout"),
+    }
+  }
+
+  #[test]
+  fn with_query_overrides_counts() {
+    let next = served(TempoArgs::default())
+      .with_query(&query(&[
+        ("count", "12"),
+        ("set", "3"),
+        ("interval", "45"),
+        ("swing-start", "1"),
+      ]))
+      .unwrap();
+    let workout = uniform(&next);
+    assert_eq!((workout.count, workout.sets, workout.interval), (12, 3, 45));
+    assert_eq!(next.swing_start, 1.0);
+  }
+
+  #[test]
+  fn with_query_merges_tempo_parts() {
+    let server = served(TempoArgs {
+      down: Some(4.0),
+      ..TempoArgs::default()
+    });
+    let next = server.with_query(&query(&[("hold", "3")])).unwrap();
+    let tempo = &uniform(&next).tempo;
+    assert_eq!(
+      (tempo.hold, tempo.down, tempo.up),
+      (Some(3.0), Some(4.0), None)
+    );
+    let resolved = next.timeline().unwrap().sets()[0].tempo();
+    assert_eq!((resolved.down, resolved.hold, resolved.up), (4.0, 3.0, 8.0));
+  }
+
+  #[test]
+  fn with_query_tempo_and_parts_replace_each_other() {
+    let with_tempo = served(TempoArgs {
+      tempo: Some("4-2-1".parse().unwrap()),
+      ..TempoArgs::default()
+    });
+    let next = with_tempo.with_query(&query(&[("hold", "3")])).unwrap();
+    let tempo = &uniform(&next).tempo;
+    assert_eq!((tempo.tempo, tempo.hold), (None, Some(3.0)));
+
+    let with_parts = served(TempoArgs {
+      hold: Some(2.0),
+      down: Some(4.0),
+      ..TempoArgs::default()
+    });
+    let next = with_parts
+      .with_query(&query(&[("tempo", "3-1-3")]))
+      .unwrap();
+    let tempo = &uniform(&next).tempo;
+    assert_eq!((tempo.hold, tempo.down, tempo.up), (None, None, None));
+    assert_eq!(tempo.tempo.map(|tempo| tempo.down), Some(3.0));
+
+    assert!(
+      with_parts
+        .with_query(&query(&[("tempo", "3-1-3"), ("up", "2")]))
+        .is_err()
+    );
+  }
+
+  #[test]
+  fn with_query_uses_the_last_repeated_key() {
+    let next = served(TempoArgs::default())
+      .with_query(&query(&[("count", "12"), ("count", "8")]))
+      .unwrap();
+    assert_eq!(uniform(&next).count, 8);
+  }
+
+  #[test]
+  fn with_query_rejects_bad_input() {
+    let server = served(TempoArgs::default());
+    for pairs in [
+      [("count", "many")],
+      [("nope", "1")],
+      [("tempo", "4-2")],
+      [("count", "0")],
+      [("count", "100000")],
+    ] {
+      assert!(
+        server.with_query(&query(&pairs)).is_err(),
+        "{:?} accepted",
+        pairs
+      );
+    }
+    let group = WebWorkout {
+      group: true,
+      ..server.clone()
+    };
+    assert!(group.with_query(&query(&[("count", "5")])).is_err());
+    assert!(group.with_query(&query(&[("freq", "5")])).is_ok());
+  }
+}